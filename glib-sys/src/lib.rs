@@ -6,7 +6,7 @@
 
 extern crate libc;
 
-use libc::{c_void, c_int, c_uint, c_float, c_double, c_char, c_uchar, c_long, c_ulong, size_t};
+use libc::{c_void, c_int, c_uint, c_ushort, c_float, c_double, c_char, c_uchar, c_long, c_ulong, size_t};
 
 pub type GQuark = u32;
 
@@ -19,13 +19,74 @@ pub const GTRUE:   c_int = 1;
 pub type gpointer = *mut c_void;
 
 pub type GSourceFunc = extern "C" fn(user_data: gpointer) -> Gboolean;
+pub type GDestroyNotify = extern "C" fn(data: gpointer);
 pub type GCallback = extern "C" fn();
 pub type GClosureNotify = extern "C" fn(data: gpointer, closure: gpointer);
+pub type GClosureMarshal = extern "C" fn(closure: *mut C_GClosure, return_value: *mut C_GValue, n_param_values: c_uint,
+    param_values: *const C_GValue, invocation_hint: gpointer, marshal_data: gpointer);
+
+#[cfg(unix)]
+pub type GPid = c_int;
+#[cfg(windows)]
+pub type GPid = *mut c_void;
+
+pub type GChildWatchFunc = extern "C" fn(pid: GPid, status: c_int, user_data: gpointer);
+
+pub type GBaseInitFunc = extern "C" fn(g_class: gpointer);
+pub type GBaseFinalizeFunc = extern "C" fn(g_class: gpointer);
+pub type GClassInitFunc = extern "C" fn(g_class: gpointer, class_data: gpointer);
+pub type GClassFinalizeFunc = extern "C" fn(g_class: gpointer, class_data: gpointer);
+pub type GInstanceInitFunc = extern "C" fn(instance: *mut C_GTypeInstance, g_class: gpointer);
+
+pub type GTypeFlags = c_uint;
+pub const G_TYPE_FLAG_ABSTRACT: GTypeFlags = 1 << 4;
+pub const G_TYPE_FLAG_VALUE_ABSTRACT: GTypeFlags = 1 << 5;
+
+pub type GSignalFlags = c_uint;
+pub const G_SIGNAL_RUN_FIRST: GSignalFlags = 1 << 0;
+pub const G_SIGNAL_RUN_LAST: GSignalFlags = 1 << 1;
+pub const G_SIGNAL_RUN_CLEANUP: GSignalFlags = 1 << 2;
+pub const G_SIGNAL_NO_RECURSE: GSignalFlags = 1 << 3;
+pub const G_SIGNAL_DETAILED: GSignalFlags = 1 << 4;
+pub const G_SIGNAL_ACTION: GSignalFlags = 1 << 5;
+pub const G_SIGNAL_NO_HOOKS: GSignalFlags = 1 << 6;
+pub const G_SIGNAL_MUST_COLLECT: GSignalFlags = 1 << 7;
+pub const G_SIGNAL_DEPRECATED: GSignalFlags = 1 << 8;
+
+pub const G_SIGNAL_TYPE_STATIC_SCOPE: GType = 1;
+
+pub type GWeakNotify = extern "C" fn(data: gpointer, where_the_object_was: *mut C_GObject);
+
+pub type GPollFunc = unsafe extern "C" fn(ufds: *mut C_GPollFD, nfds: c_uint, timeout_: c_int) -> c_int;
+
+pub type GIOCondition = c_uint;
+pub const G_IO_IN: GIOCondition = 1;
+pub const G_IO_OUT: GIOCondition = 4;
+pub const G_IO_PRI: GIOCondition = 2;
+pub const G_IO_ERR: GIOCondition = 8;
+pub const G_IO_HUP: GIOCondition = 16;
+pub const G_IO_NVAL: GIOCondition = 32;
+
+pub type GParamFlags = c_uint;
+pub const G_PARAM_READABLE: GParamFlags = 1 << 0;
+pub const G_PARAM_WRITABLE: GParamFlags = 1 << 1;
+pub const G_PARAM_READWRITE: GParamFlags = G_PARAM_READABLE | G_PARAM_WRITABLE;
+pub const G_PARAM_CONSTRUCT: GParamFlags = 1 << 2;
+pub const G_PARAM_CONSTRUCT_ONLY: GParamFlags = 1 << 3;
+pub const G_PARAM_LAX_VALIDATION: GParamFlags = 1 << 4;
+pub const G_PARAM_STATIC_NAME: GParamFlags = 1 << 5;
+pub const G_PARAM_STATIC_NICK: GParamFlags = 1 << 6;
+pub const G_PARAM_STATIC_BLURB: GParamFlags = 1 << 7;
+pub const G_PARAM_EXPLICIT_NOTIFY: GParamFlags = 1 << 30;
+pub const G_PARAM_DEPRECATED: GParamFlags = 1 << 31;
+
+#[cfg(unix)]
+pub type GUnixFDSourceFunc = extern "C" fn(fd: c_int, condition: GIOCondition, user_data: gpointer) -> Gboolean;
 
 #[repr(C)]
 pub struct C_GValue {
-    type_: GType,
-    data: [size_t; 2],
+    pub type_: GType,
+    pub data: [size_t; 2],
 }
 
 #[repr(C)]
@@ -52,7 +113,96 @@ pub struct C_GError {
 pub struct C_GPermission;
 
 #[repr(C)]
-pub struct C_GObject;
+pub struct C_GTypeClass {
+    pub g_type: GType,
+}
+
+#[repr(C)]
+pub struct C_GTypeInstance {
+    pub g_class: *mut C_GTypeClass,
+}
+
+#[repr(C)]
+pub struct C_GObject {
+    pub g_type_instance: C_GTypeInstance,
+    pub ref_count: c_uint,
+    pub qdata: gpointer,
+}
+
+#[repr(C)]
+pub struct C_GObjectClass {
+    pub g_type_class: C_GTypeClass,
+    pub construct_properties: *mut C_GSList,
+    pub constructor: Option<extern "C" fn(type_: GType, n_construct_properties: c_uint,
+        construct_properties: gpointer) -> *mut C_GObject>,
+    pub set_property: Option<extern "C" fn(object: *mut C_GObject, property_id: c_uint, value: *const C_GValue,
+        pspec: *mut C_GParamSpec)>,
+    pub get_property: Option<extern "C" fn(object: *mut C_GObject, property_id: c_uint, value: *mut C_GValue,
+        pspec: *mut C_GParamSpec)>,
+    pub dispose: Option<extern "C" fn(object: *mut C_GObject)>,
+    pub finalize: Option<extern "C" fn(object: *mut C_GObject)>,
+    pub dispatch_properties_changed: Option<extern "C" fn(object: *mut C_GObject, n_pspecs: c_uint,
+        pspecs: *mut *mut C_GParamSpec)>,
+    pub notify: Option<extern "C" fn(object: *mut C_GObject, pspec: *mut C_GParamSpec)>,
+    pub constructed: Option<extern "C" fn(object: *mut C_GObject)>,
+    pub flags: size_t,
+    pub pdummy: [gpointer; 6],
+}
+
+#[repr(C)]
+pub struct C_GTypeInfo {
+    pub class_size: u16,
+    pub base_init: Option<GBaseInitFunc>,
+    pub base_finalize: Option<GBaseFinalizeFunc>,
+    pub class_init: Option<GClassInitFunc>,
+    pub class_finalize: Option<GClassFinalizeFunc>,
+    pub class_data: gpointer,
+    pub instance_size: u16,
+    pub n_preallocs: u16,
+    pub instance_init: Option<GInstanceInitFunc>,
+    pub value_table: gpointer,
+}
+
+#[repr(C)]
+pub struct C_GSignalQuery {
+    pub signal_id: c_uint,
+    pub signal_name: *const c_char,
+    pub itype: GType,
+    pub signal_flags: GSignalFlags,
+    pub return_type: GType,
+    pub n_params: c_uint,
+    pub param_types: *const GType,
+}
+
+#[repr(C)]
+pub struct C_GTypeQuery {
+    pub type_: GType,
+    pub type_name: *const c_char,
+    pub class_size: c_uint,
+    pub instance_size: c_uint,
+}
+
+#[repr(C)]
+pub struct C_GClosure {
+    pub flags: c_uint,
+    pub marshal: Option<GClosureMarshal>,
+    pub data: gpointer,
+    pub notifiers: gpointer,
+}
+
+#[repr(C)]
+pub struct C_GWeakRef {
+    pub priv_: gpointer,
+}
+
+#[repr(C)]
+pub struct C_GParamSpec {
+    pub g_type_instance: C_GTypeInstance,
+    pub name: *const c_char,
+    pub flags: GParamFlags,
+    pub value_type: GType,
+    pub owner_type: GType,
+}
 
 #[repr(C)]
 pub struct C_GMainLoop;
@@ -61,13 +211,70 @@ pub struct C_GMainLoop;
 pub struct C_GMainContext;
 
 #[repr(C)]
-pub struct C_GSource;
+pub struct C_GSource {
+    pub callback_data: gpointer,
+    pub callback_funcs: gpointer,
+    pub source_funcs: *const C_GSourceFuncs,
+    pub ref_count: c_uint,
+    pub context: *mut C_GMainContext,
+    pub priority: c_int,
+    pub flags: c_uint,
+    pub source_id: c_uint,
+    pub poll_fds: *mut C_GSList,
+    pub prev: *mut C_GSource,
+    pub next: *mut C_GSource,
+    pub name: *mut c_char,
+    pub priv_: gpointer,
+}
+
+#[repr(C)]
+pub struct C_GSourceFuncs {
+    pub prepare: Option<extern "C" fn(source: *mut C_GSource, timeout_: *mut c_int) -> Gboolean>,
+    pub check: Option<extern "C" fn(source: *mut C_GSource) -> Gboolean>,
+    pub dispatch: Option<extern "C" fn(source: *mut C_GSource, callback: Option<GSourceFunc>,
+        user_data: gpointer) -> Gboolean>,
+    pub finalize: Option<extern "C" fn(source: *mut C_GSource)>,
+    pub closure_callback: Option<GSourceFunc>,
+    pub closure_marshal: Option<extern "C" fn()>,
+}
+
 
+#[cfg(not(all(windows, target_pointer_width = "64")))]
 #[repr(C)]
-pub struct C_GPid;
+pub struct C_GPollFD {
+    pub fd: c_int,
+    pub events: c_ushort,
+    pub revents: c_ushort,
+}
 
+// GLib keeps the whole HANDLE on 64-bit Windows
+#[cfg(all(windows, target_pointer_width = "64"))]
 #[repr(C)]
-pub struct C_GPollFD;
+pub struct C_GPollFD {
+    pub fd: i64,
+    pub events: c_ushort,
+    pub revents: c_ushort,
+}
+
+//=========================================================================
+// GSource priorities
+//=========================================================================
+
+pub const G_PRIORITY_HIGH: c_int = -100;
+pub const G_PRIORITY_DEFAULT: c_int = 0;
+pub const G_PRIORITY_HIGH_IDLE: c_int = 100;
+pub const G_PRIORITY_DEFAULT_IDLE: c_int = 200;
+pub const G_PRIORITY_LOW: c_int = 300;
+
+//=========================================================================
+// Log levels
+//=========================================================================
+
+pub type GLogLevelFlags = c_int;
+
+pub const G_LOG_LEVEL_ERROR: GLogLevelFlags = 1 << 2;
+pub const G_LOG_LEVEL_CRITICAL: GLogLevelFlags = 1 << 3;
+pub const G_LOG_LEVEL_WARNING: GLogLevelFlags = 1 << 4;
 
 //=========================================================================
 // GType constants
@@ -173,6 +380,11 @@ extern "C" {
     //pub fn g_prefix_error                 (err: **C_GError, format: *c_char, ...) -> ();
     //pub fn g_propagate_prefixed_error     (dest: **C_GError, src: *C_GError, format: *c_char, ...) -> ();
 
+    //=========================================================================
+    // Message logging
+    //=========================================================================
+    pub fn g_log                          (log_domain: *const c_char, log_level: GLogLevelFlags, format: *const c_char, ...);
+
     //=========================================================================
     // GPermission                                                       NOT OK
     //=========================================================================
@@ -195,11 +407,76 @@ extern "C" {
 
     //pub type GAsyncReadyCallback = Option<extern "C" fn(source_object: *mut C_GObject, res: *mut C_GAsyncResult, user_data: gpointer)>;
 
+    //=========================================================================
+    // GType
+    //=========================================================================
+    pub fn g_type_name                         (type_: GType) -> *const c_char;
+    pub fn g_type_from_name                    (name: *const c_char) -> GType;
+    pub fn g_type_parent                       (type_: GType) -> GType;
+    pub fn g_type_is_a                         (type_: GType, is_a_type: GType) -> Gboolean;
+    pub fn g_type_check_instance_is_a          (instance: *mut C_GTypeInstance, iface_type: GType) -> Gboolean;
+    pub fn g_type_class_ref                    (type_: GType) -> gpointer;
+    pub fn g_type_class_unref                  (g_class: gpointer);
+    pub fn g_type_class_peek_parent            (g_class: gpointer) -> gpointer;
+    pub fn g_type_class_adjust_private_offset  (g_class: gpointer, private_size_or_offset: *mut c_int);
+    pub fn g_type_query                        (type_: GType, query: *mut C_GTypeQuery);
+    pub fn g_type_register_static              (parent_type: GType, type_name: *const c_char, info: *const C_GTypeInfo,
+        flags: GTypeFlags) -> GType;
+    pub fn g_type_add_instance_private         (class_type: GType, private_size: size_t) -> c_int;
+
     //=========================================================================
     // GObject
     //=========================================================================
+    pub fn g_object_get_type() -> GType;
+    pub fn g_initially_unowned_get_type() -> GType;
+    pub fn g_object_new(object_type: GType, first_property_name: *const c_char, ...) -> *mut C_GObject;
+    pub fn g_object_new_with_properties(object_type: GType, n_properties: c_uint, names: *const *const c_char,
+        values: *const C_GValue) -> *mut C_GObject;
+    pub fn g_object_set_property(object: *mut C_GObject, property_name: *const c_char, value: *const C_GValue);
+    pub fn g_object_get_property(object: *mut C_GObject, property_name: *const c_char, value: *mut C_GValue);
+    pub fn g_object_freeze_notify(object: *mut C_GObject);
+    pub fn g_object_thaw_notify(object: *mut C_GObject);
+    pub fn g_object_notify(object: *mut C_GObject, property_name: *const c_char);
+    pub fn g_object_notify_by_pspec(object: *mut C_GObject, pspec: *mut C_GParamSpec);
+    pub fn g_object_weak_ref(object: *mut C_GObject, notify: GWeakNotify, data: gpointer);
+    pub fn g_object_weak_unref(object: *mut C_GObject, notify: GWeakNotify, data: gpointer);
+    pub fn g_weak_ref_init(weak_ref: *mut C_GWeakRef, object: *mut C_GObject);
+    pub fn g_weak_ref_clear(weak_ref: *mut C_GWeakRef);
+    pub fn g_weak_ref_get(weak_ref: *mut C_GWeakRef) -> *mut C_GObject;
+    pub fn g_weak_ref_set(weak_ref: *mut C_GWeakRef, object: *mut C_GObject);
+    pub fn g_object_class_find_property(oclass: *mut C_GObjectClass, property_name: *const c_char) -> *mut C_GParamSpec;
+    pub fn g_object_class_install_property(oclass: *mut C_GObjectClass, property_id: c_uint, pspec: *mut C_GParamSpec);
+    pub fn g_object_class_list_properties(oclass: *mut C_GObjectClass, n_properties: *mut c_uint) -> *mut *mut C_GParamSpec;
+
+    //=========================================================================
+    // GParamSpec
+    //=========================================================================
+    pub fn g_param_spec_ref                    (pspec: *mut C_GParamSpec) -> *mut C_GParamSpec;
+    pub fn g_param_spec_unref                  (pspec: *mut C_GParamSpec);
+    pub fn g_param_spec_ref_sink               (pspec: *mut C_GParamSpec) -> *mut C_GParamSpec;
+    pub fn g_param_spec_get_name               (pspec: *mut C_GParamSpec) -> *const c_char;
+    pub fn g_param_spec_get_nick               (pspec: *mut C_GParamSpec) -> *const c_char;
+    pub fn g_param_spec_get_blurb              (pspec: *mut C_GParamSpec) -> *const c_char;
+    pub fn g_param_spec_boolean                (name: *const c_char, nick: *const c_char, blurb: *const c_char,
+        default_value: Gboolean, flags: GParamFlags) -> *mut C_GParamSpec;
+    pub fn g_param_spec_int                    (name: *const c_char, nick: *const c_char, blurb: *const c_char,
+        minimum: c_int, maximum: c_int, default_value: c_int, flags: GParamFlags) -> *mut C_GParamSpec;
+    pub fn g_param_spec_uint                   (name: *const c_char, nick: *const c_char, blurb: *const c_char,
+        minimum: c_uint, maximum: c_uint, default_value: c_uint, flags: GParamFlags) -> *mut C_GParamSpec;
+    pub fn g_param_spec_int64                  (name: *const c_char, nick: *const c_char, blurb: *const c_char,
+        minimum: i64, maximum: i64, default_value: i64, flags: GParamFlags) -> *mut C_GParamSpec;
+    pub fn g_param_spec_uint64                 (name: *const c_char, nick: *const c_char, blurb: *const c_char,
+        minimum: u64, maximum: u64, default_value: u64, flags: GParamFlags) -> *mut C_GParamSpec;
+    pub fn g_param_spec_double                 (name: *const c_char, nick: *const c_char, blurb: *const c_char,
+        minimum: c_double, maximum: c_double, default_value: c_double, flags: GParamFlags) -> *mut C_GParamSpec;
+    pub fn g_param_spec_string                 (name: *const c_char, nick: *const c_char, blurb: *const c_char,
+        default_value: *const c_char, flags: GParamFlags) -> *mut C_GParamSpec;
+    pub fn g_param_spec_object                 (name: *const c_char, nick: *const c_char, blurb: *const c_char,
+        object_type: GType, flags: GParamFlags) -> *mut C_GParamSpec;
     pub fn g_object_ref(object: *mut C_GObject) -> *mut C_GObject;
     pub fn g_object_unref(object: *mut C_GObject);
+    pub fn g_object_ref_sink(object: *mut C_GObject) -> *mut C_GObject;
+    pub fn g_object_is_floating(object: *mut C_GObject) -> Gboolean;
 
     pub fn glue_signal_connect(g_object: *mut C_GObject,
                                signal: *const c_char,
@@ -213,6 +490,8 @@ extern "C" {
     pub fn get_gtype                           (_type: GType) -> GType;
     pub fn g_value_init                        (value: *mut C_GValue, _type: GType);
     pub fn g_value_reset                       (value: *mut C_GValue);
+    pub fn g_value_copy                        (src_value: *const C_GValue, dest_value: *mut C_GValue);
+    pub fn g_value_transform                   (src_value: *const C_GValue, dest_value: *mut C_GValue) -> Gboolean;
     pub fn g_value_unset                       (value: *mut C_GValue);
     pub fn g_strdup_value_contents             (value: *mut C_GValue) -> *mut c_char;
     pub fn g_value_set_boolean                 (value: *mut C_GValue, b: Gboolean);
@@ -257,6 +536,7 @@ extern "C" {
     pub fn g_value_get_gtype                   (value: *const C_GValue) -> GType;
     pub fn g_value_type_compatible             (src_type: GType, dest_type: GType) -> Gboolean;
     pub fn g_value_type_transformable          (src_type: GType, dest_type: GType) -> Gboolean;
+    pub fn g_gtype_get_type                    () -> GType;
 
     //=========================================================================
     // GMainLoop
@@ -287,20 +567,21 @@ extern "C" {
     pub fn g_main_context_is_owner             (context: *mut C_GMainContext) -> Gboolean;
     //pub fn g_main_context_wait                 (context: *mut C_GMainContext, cond: *mut C_GCond, mutex: *mut C_GMutex) -> Gboolean;
     pub fn g_main_context_prepare              (context: *mut C_GMainContext, priority: *mut c_int) -> Gboolean;
-    //pub fn g_main_context_query                (context: *mut C_GMainContext, max_priority: c_int, timeout_: *mut c_int, fds: *mut C_GPollFD,
-    //    n_fds: c_int) -> c_int;
-    //pub fn g_main_context_check                (context: *mut C_GMainContext, max_priority: c_int, fds: *mut C_GPollFD,
-    //    n_fds: c_int) -> c_int;
+    pub fn g_main_context_query                (context: *mut C_GMainContext, max_priority: c_int, timeout_: *mut c_int, fds: *mut C_GPollFD,
+        n_fds: c_int) -> c_int;
+    pub fn g_main_context_check                (context: *mut C_GMainContext, max_priority: c_int, fds: *mut C_GPollFD,
+        n_fds: c_int) -> Gboolean;
     pub fn g_main_context_dispatch             (context: *mut C_GMainContext);
-    //pub fn g_main_context_set_poll_func        ();
-    //pub fn g_main_context_get_poll_func        ();
+    pub fn g_main_context_set_poll_func        (context: *mut C_GMainContext, func: Option<GPollFunc>);
+    pub fn g_main_context_get_poll_func        (context: *mut C_GMainContext) -> Option<GPollFunc>;
     pub fn g_main_context_add_poll             (context: *mut C_GMainContext, fd: *mut C_GPollFD, priority: c_int);
     pub fn g_main_context_remove_poll          (context: *mut C_GMainContext, fd: *mut C_GPollFD);
     pub fn g_main_depth                        () -> c_int;
 
     pub fn g_main_current_source               () -> *mut C_GSource;
     //pub fn g_main_context_invoke               ();
-    //pub fn g_main_context_invoke_full          ();
+    pub fn g_main_context_invoke_full          (context: *mut C_GMainContext, priority: c_int, function: GSourceFunc,
+        data: gpointer, notify: GDestroyNotify);
     pub fn g_main_context_get_thread_default   () -> *mut C_GMainContext;
     pub fn g_main_context_ref_thread_default   () -> *mut C_GMainContext;
     pub fn g_main_context_push_thread_default  (context: *mut C_GMainContext);
@@ -309,27 +590,29 @@ extern "C" {
     //=========================================================================
     // GSource
     //=========================================================================
-    pub fn g_timeout_source_new                () -> *mut C_GSource;
+    pub fn g_timeout_source_new                (interval: c_uint) -> *mut C_GSource;
     pub fn g_timeout_source_new_seconds        (interval: c_uint) -> *mut C_GSource;
-    //pub fn g_timeout_add                       (interval: c_uint, function: GSourceFunc, data: gpointer) -> c_uint;
-    pub fn g_timeout_add                       (interval: c_uint, function: gpointer, data: gpointer) -> c_uint;
-    //pub fn g_timeout_add_full                  ();
-    //pub fn g_timeout_add_seconds               (interval: c_uint, function: GSourceFunc, data: gpointer) -> c_uint;
-    pub fn g_timeout_add_seconds               (interval: c_uint, function: gpointer, data: gpointer) -> c_uint;
-    //pub fn g_timeout_add_seconds_full          ();
+    pub fn g_timeout_add                       (interval: c_uint, function: GSourceFunc, data: gpointer) -> c_uint;
+    pub fn g_timeout_add_full                  (priority: c_int, interval: c_uint, function: GSourceFunc, data: gpointer,
+        notify: GDestroyNotify) -> c_uint;
+    pub fn g_timeout_add_seconds               (interval: c_uint, function: GSourceFunc, data: gpointer) -> c_uint;
+    pub fn g_timeout_add_seconds_full          (priority: c_int, interval: c_uint, function: GSourceFunc, data: gpointer,
+        notify: GDestroyNotify) -> c_uint;
     pub fn g_idle_source_new                   () -> *mut C_GSource;
     //pub fn g_idle_add                          ();
-    //pub fn g_idle_add_full                     ();
+    pub fn g_idle_add_full                     (priority: c_int, function: GSourceFunc, data: gpointer,
+        notify: GDestroyNotify) -> c_uint;
     pub fn g_idle_remove_by_data               (data: gpointer) -> Gboolean;
-    pub fn g_child_watch_source_new            (pid: C_GPid) -> *mut C_GSource;
+    pub fn g_child_watch_source_new            (pid: GPid) -> *mut C_GSource;
     //pub fn g_child_watch_add                   ();
-    //pub fn g_child_watch_add_full              ();
+    pub fn g_child_watch_add_full              (priority: c_int, pid: GPid, function: GChildWatchFunc, data: gpointer,
+        notify: GDestroyNotify) -> c_uint;
     pub fn g_poll                              (fds: *mut C_GPollFD, nfds: c_uint, timeout: c_int) -> c_int;
-    //pub fn g_source_new                        ();
+    pub fn g_source_new                        (source_funcs: *mut C_GSourceFuncs, struct_size: c_uint) -> *mut C_GSource;
     pub fn g_source_ref                        (source: *mut C_GSource) -> *mut C_GSource;
     pub fn g_source_unref                      (source: *mut C_GSource);
-    //pub fn g_source_set_funcs                  ();
-    pub fn g_source_attach                     (source: *mut C_GSource, context: *mut C_GMainContext);
+    pub fn g_source_set_funcs                  (source: *mut C_GSource, funcs: *mut C_GSourceFuncs);
+    pub fn g_source_attach                     (source: *mut C_GSource, context: *mut C_GMainContext) -> c_uint;
     pub fn g_source_destroy                    (source: *mut C_GSource);
     pub fn g_source_is_destroyed               (source: *mut C_GSource) -> Gboolean;
     pub fn g_source_set_priority               (source: *mut C_GSource, priority: c_int);
@@ -341,14 +624,10 @@ extern "C" {
     pub fn g_source_set_name                   (source: *mut C_GSource, name: *const c_char);
     pub fn g_source_set_name_by_id             (tag: c_uint, name: *const c_char);
     pub fn g_source_get_context                (source: *mut C_GSource) -> *mut C_GMainContext;
-    //pub fn g_source_set_callback               ();
+    pub fn g_source_set_callback               (source: *mut C_GSource, func: GSourceFunc, data: gpointer, notify: GDestroyNotify);
     //pub fn g_source_set_callback_indirect      ();
     pub fn g_source_set_ready_time             (source: *mut C_GSource, ready_time: i64);
     pub fn g_source_get_ready_time             (source: *mut C_GSource) -> i64;
-    //pub fn g_source_add_unix_fd                ();
-    //pub fn g_source_remove_unix_fd             ();
-    //pub fn g_source_modify_unix_fd             ();
-    //pub fn g_source_query_unix_fd              ();
     pub fn g_source_add_poll                   (source: *mut C_GSource, fd: *mut C_GPollFD);
     pub fn g_source_remove_poll                (source: *mut C_GSource, fd: *mut C_GPollFD);
     pub fn g_source_add_child_source           (source: *mut C_GSource, child_source: *mut C_GSource);
@@ -358,10 +637,58 @@ extern "C" {
     //pub fn g_source_remove_by_funcs_user_data  ();
     pub fn g_source_remove_by_user_data        (user_data: gpointer) -> Gboolean;
 
+    //=========================================================================
+    // GClosure
+    //=========================================================================
+    pub fn g_closure_new_simple                (sizeof_closure: c_uint, data: gpointer) -> *mut C_GClosure;
+    pub fn g_closure_ref                       (closure: *mut C_GClosure) -> *mut C_GClosure;
+    pub fn g_closure_unref                     (closure: *mut C_GClosure);
+    pub fn g_closure_sink                      (closure: *mut C_GClosure);
+    pub fn g_closure_invalidate                (closure: *mut C_GClosure);
+    pub fn g_closure_set_marshal               (closure: *mut C_GClosure, marshal: GClosureMarshal);
+    pub fn g_closure_add_finalize_notifier     (closure: *mut C_GClosure, notify_data: gpointer, notify_func: GClosureNotify);
+    pub fn g_closure_invoke                    (closure: *mut C_GClosure, return_value: *mut C_GValue, n_param_values: c_uint,
+        param_values: *const C_GValue, invocation_hint: gpointer);
+
     //=========================================================================
     // GSignal
     //=========================================================================
     pub fn g_signal_connect_data(instance: gpointer, detailed_signal: *const c_char,
                                  c_handler: GCallback, data: gpointer,
                                  destroy_data: GClosureNotify, connect_flags: c_int) -> c_ulong;
+    pub fn g_signal_connect_closure(instance: gpointer, detailed_signal: *const c_char,
+                                    closure: *mut C_GClosure, after: Gboolean) -> c_ulong;
+    pub fn g_signal_handler_disconnect(instance: gpointer, handler_id: c_ulong);
+    pub fn g_signal_handler_block(instance: gpointer, handler_id: c_ulong);
+    pub fn g_signal_handler_unblock(instance: gpointer, handler_id: c_ulong);
+    pub fn g_signal_handler_is_connected(instance: gpointer, handler_id: c_ulong) -> Gboolean;
+    pub fn g_signal_lookup(name: *const c_char, itype: GType) -> c_uint;
+    pub fn g_signal_list_ids(itype: GType, n_ids: *mut c_uint) -> *mut c_uint;
+    pub fn g_signal_query(signal_id: c_uint, query: *mut C_GSignalQuery);
+    pub fn g_signal_parse_name(detailed_signal: *const c_char, itype: GType, signal_id_p: *mut c_uint,
+                               detail_p: *mut GQuark, force_detail_quark: Gboolean) -> Gboolean;
+    pub fn g_signal_emitv(instance_and_params: *const C_GValue, signal_id: c_uint, detail: GQuark,
+                          return_value: *mut C_GValue);
+    pub fn g_signal_newv(signal_name: *const c_char, itype: GType, signal_flags: GSignalFlags,
+                         class_closure: *mut C_GClosure, accumulator: gpointer, accu_data: gpointer,
+                         c_marshaller: gpointer, return_type: GType, n_params: c_uint,
+                         param_types: *mut GType) -> c_uint;
+}
+
+#[cfg(unix)]
+extern "C" {
+
+    //=========================================================================
+    // GSource (UNIX specific)
+    //=========================================================================
+    pub fn g_source_add_unix_fd                (source: *mut C_GSource, fd: c_int, events: GIOCondition) -> gpointer;
+    pub fn g_source_remove_unix_fd             (source: *mut C_GSource, tag: gpointer);
+    pub fn g_source_modify_unix_fd             (source: *mut C_GSource, tag: gpointer, new_events: GIOCondition);
+    pub fn g_source_query_unix_fd              (source: *mut C_GSource, tag: gpointer) -> GIOCondition;
+    pub fn g_unix_fd_source_new                (fd: c_int, condition: GIOCondition) -> *mut C_GSource;
+    pub fn g_unix_fd_add_full                  (priority: c_int, fd: c_int, condition: GIOCondition,
+        function: GUnixFDSourceFunc, user_data: gpointer, notify: GDestroyNotify) -> c_uint;
+    pub fn g_unix_signal_source_new            (signum: c_int) -> *mut C_GSource;
+    pub fn g_unix_signal_add_full              (priority: c_int, signum: c_int, handler: GSourceFunc, user_data: gpointer,
+        notify: GDestroyNotify) -> c_uint;
 }
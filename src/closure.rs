@@ -0,0 +1,179 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+//! GClosure — Callbacks with `Value` arguments
+
+use std::ffi::CString;
+use std::mem;
+use std::ptr;
+use std::slice;
+use ffi;
+use translate::{FromGlibPtrNotNull, Stash, ToGlibPtr, from_glib};
+use type_::Type;
+use value::{ToValue, Value};
+
+/// A reference counted `GClosure` calling a Rust closure
+///
+/// The Rust closure is dropped when the last reference is released.
+pub struct Closure {
+    pointer: *mut ffi::C_GClosure,
+}
+
+impl Closure {
+    /// Creates a closure calling `f`
+    ///
+    /// `f` gets the parameter values and returns the return value, if the
+    /// caller expects one. A returned value of a different type is converted
+    /// if possible, otherwise a critical warning is logged.
+    pub fn new<F: Fn(&[Value]) -> Option<Value> + 'static>(f: F) -> Closure {
+        unsafe {
+            let data = Box::into_raw(Box::new(f)) as ffi::gpointer;
+            let ptr = ffi::g_closure_new_simple(mem::size_of::<ffi::C_GClosure>() as u32, data);
+            ffi::g_closure_add_finalize_notifier(ptr, data, finalize::<F>);
+            ffi::g_closure_set_marshal(ptr, marshal::<F>);
+            ffi::g_closure_ref(ptr);
+            ffi::g_closure_sink(ptr);
+            Closure { pointer: ptr }
+        }
+    }
+
+    /// Calls the closure
+    ///
+    /// Returns `None` if `return_type` is `Type::Unit`.
+    pub fn invoke(&self, return_type: Type, args: &[&dyn ToValue]) -> Option<Value> {
+        let args: Vec<Value> = args.iter().map(|arg| arg.to_value()).collect();
+        let mut ret = if return_type == Type::Unit { None } else { Some(Value::from_type(return_type)) };
+        unsafe {
+            let ret_ptr = match ret {
+                Some(ref mut ret) => ret.as_mut_ptr(),
+                None => ptr::null_mut(),
+            };
+            ffi::g_closure_invoke(self.pointer, ret_ptr, args.len() as u32,
+                args.as_ptr() as *const ffi::C_GValue, ptr::null_mut());
+        }
+        ret
+    }
+
+    /// Prevents the closure from being called again
+    ///
+    /// Signal handlers using the closure are disconnected.
+    pub fn invalidate(&self) {
+        unsafe { ffi::g_closure_invalidate(self.pointer) }
+    }
+}
+
+extern "C" fn marshal<F: Fn(&[Value]) -> Option<Value> + 'static>(closure: *mut ffi::C_GClosure,
+        return_value: *mut ffi::C_GValue, n_param_values: u32, param_values: *const ffi::C_GValue,
+        _: ffi::gpointer, _: ffi::gpointer) {
+    unsafe {
+        let f = &*((*closure).data as *const F);
+        let args = if n_param_values == 0 { &[][..] }
+            else { slice::from_raw_parts(param_values as *const Value, n_param_values as usize) };
+        let res = f(args);
+        if return_value.is_null() {
+            return;
+        }
+        let return_value = &mut *(return_value as *mut Value);
+        if let Some(res) = res {
+            if Value::compatible(res.value_type(), return_value.value_type()) {
+                ffi::g_value_copy(res.as_ptr(), return_value.as_mut_ptr());
+            }
+            else if !from_glib::<_, bool>(ffi::g_value_transform(res.as_ptr(), return_value.as_mut_ptr())) {
+                // can't unwind into GLib, the return value is left unset
+                let message = CString::new(format!("Closure returned a value of type '{}' instead of '{}'",
+                    res.value_type().name(), return_value.value_type().name())).unwrap();
+                ffi::g_log(b"GLib-Rust\0".as_ptr() as *const _, ffi::G_LOG_LEVEL_CRITICAL,
+                    b"%s\0".as_ptr() as *const _, message.as_ptr());
+            }
+        }
+    }
+}
+
+extern "C" fn finalize<F: Fn(&[Value]) -> Option<Value> + 'static>(data: ffi::gpointer, _: ffi::gpointer) {
+    unsafe { drop(Box::from_raw(data as *mut F)) }
+}
+
+impl Clone for Closure {
+    fn clone(&self) -> Closure {
+        unsafe { FromGlibPtrNotNull::borrow(self.pointer) }
+    }
+}
+
+impl Drop for Closure {
+    fn drop(&mut self) {
+        unsafe { ffi::g_closure_unref(self.pointer) }
+    }
+}
+
+impl <'a> ToGlibPtr<'a, *mut ffi::C_GClosure> for Closure {
+    type Storage = &'a Closure;
+
+    #[inline]
+    fn borrow_to_glib(&'a self) -> Stash<*mut ffi::C_GClosure, Closure> {
+        Stash(self.pointer, self)
+    }
+}
+
+impl FromGlibPtrNotNull<*mut ffi::C_GClosure> for Closure {
+    unsafe fn borrow(ptr: *mut ffi::C_GClosure) -> Closure {
+        debug_assert!(!ptr.is_null());
+        Closure { pointer: ffi::g_closure_ref(ptr) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GClosure) -> Closure {
+        debug_assert!(!ptr.is_null());
+        Closure { pointer: ptr }
+    }
+
+    unsafe fn sink(ptr: *mut ffi::C_GClosure) -> Closure {
+        debug_assert!(!ptr.is_null());
+        ffi::g_closure_ref(ptr);
+        ffi::g_closure_sink(ptr);
+        Closure { pointer: ptr }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use type_::Type;
+    use value::{ToValue, Value};
+    use super::Closure;
+
+    #[test]
+    fn invoke() {
+        let closure = Closure::new(|args| {
+            let sum = args.iter().map(|arg| arg.get::<i32>()).sum::<i32>();
+            Some(sum.to_value())
+        });
+        let res = closure.invoke(Type::I64, &[&1, &2, &3]).unwrap();
+        assert_eq!(res.value_type(), Type::I64);
+        assert_eq!(res.get::<i64>(), 6);
+        assert!(closure.invoke(Type::Unit, &[]).is_none());
+    }
+
+    #[test]
+    fn dropped_with_last_reference() {
+        struct DropFlag(Rc<Cell<bool>>);
+
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.set(true);
+            }
+        }
+
+        let dropped = Rc::new(Cell::new(false));
+        let flag = DropFlag(dropped.clone());
+        let closure = Closure::new(move |_: &[Value]| {
+            let _ = &flag;
+            None
+        });
+        let clone = closure.clone();
+        drop(closure);
+        assert!(!dropped.get());
+        drop(clone);
+        assert!(dropped.get());
+    }
+}
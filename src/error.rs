@@ -2,9 +2,12 @@
 // See the COPYRIGHT file at the top-level directory of this distribution.
 // Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
 
+use std::error;
+use std::fmt;
 use ffi::{self, GQuark};
 use glib_container::GlibContainer;
 use translate::ToGlibPtr;
+use type_::Type;
 
 pub struct Error {
     pointer: *mut ffi::C_GError
@@ -79,3 +82,84 @@ impl GlibContainer<*mut ffi::C_GError> for Error {
         self.pointer
     }
 }
+
+/// An error returned by functions that only report whether they failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BoolError(pub &'static str);
+
+impl fmt::Display for BoolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl error::Error for BoolError {
+    fn description(&self) -> &str {
+        self.0
+    }
+}
+
+/// An error returned when getting or setting object properties
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PropertyError {
+    /// The object has no property of that name
+    NotFound(String),
+    /// The property can't be read
+    NotReadable(String),
+    /// The property can't be written
+    NotWritable(String),
+    /// The property can only be set when the object is constructed
+    ConstructOnly(String),
+    /// The value can't be converted to the type of the property
+    WrongType {
+        name: String,
+        expected: Type,
+        actual: Type,
+    },
+}
+
+impl fmt::Display for PropertyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PropertyError::NotFound(ref name) => write!(f, "Property '{}' not found", name),
+            PropertyError::NotReadable(ref name) => write!(f, "Property '{}' is not readable", name),
+            PropertyError::NotWritable(ref name) => write!(f, "Property '{}' is not writable", name),
+            PropertyError::ConstructOnly(ref name) =>
+                write!(f, "Property '{}' can only be set at construction", name),
+            PropertyError::WrongType { ref name, ref expected, ref actual } =>
+                write!(f, "Property '{}' of type '{}' can't be set from a value of type '{}'",
+                    name, expected.name(), actual.name()),
+        }
+    }
+}
+
+impl error::Error for PropertyError {
+    fn description(&self) -> &str {
+        "Invalid property access"
+    }
+}
+
+/// An error returned when connecting to signals
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignalError {
+    /// The type has no signal of that name
+    NotFound {
+        name: String,
+        type_: Type,
+    },
+}
+
+impl fmt::Display for SignalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SignalError::NotFound { ref name, ref type_ } =>
+                write!(f, "Signal '{}' not found on type '{}'", name, type_.name()),
+        }
+    }
+}
+
+impl error::Error for SignalError {
+    fn description(&self) -> &str {
+        "Invalid signal"
+    }
+}
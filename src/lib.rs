@@ -10,20 +10,43 @@ Bindings and wrappers for __GLib__
 #![feature(test)]
 
 extern crate libc;
+#[macro_use]
+extern crate bitflags;
+extern crate futures_core;
 extern crate glib_sys as glib_ffi;
 
 pub use glib_ffi as ffi;
 
-use libc::c_char;
-
 pub use self::list::{List, Elem, RevElem};
 pub use self::slist::{SList, SElem};
 pub use self::glib_container::GlibContainer;
-pub use self::error::{Error};
+pub use self::error::{Error, BoolError, PropertyError, SignalError};
 pub use self::permission::Permission;
+pub use self::main_context::{MainContext, MainContextAcquireGuard, ThreadDefaultGuard, PollFD};
+pub use self::main_context_channel::{Sender, Receiver};
+pub use self::main_context_futures::{JoinHandle, Aborted};
+pub use self::main_loop::MainLoop;
+pub use self::closure::Closure;
+pub use self::object::{Object, ObjectType, IsA, Cast, ObjectExt, NotifyFreezeGuard, SignalBlockGuard, WeakRef};
+pub use self::signal::{SignalHandlerId, SignalQuery};
+pub use self::param_spec::{ParamSpec, ParamFlags};
+pub use self::subclass::{TypeClass, TypeInstance};
+pub use self::param_spec::{PARAM_READABLE, PARAM_WRITABLE, PARAM_READWRITE, PARAM_CONSTRUCT, PARAM_CONSTRUCT_ONLY};
+pub use self::source_futures::{TimeoutFuture, IntervalStream, timeout_future, interval_stream};
+pub use self::source::{Continue, SourceId, Priority, timeout_add, timeout_add_seconds};
+pub use self::source::{idle_add, idle_add_with_priority, idle_add_local, idle_add_local_with_priority};
+pub use self::source::{Source, SourceFuncs};
+pub use self::source::{Pid, child_watch_add, child_watch_source_new};
+pub use self::source::{timeout_source_new, timeout_source_new_seconds, idle_source_new};
+pub use self::source::{IOCondition, IO_IN, IO_OUT, IO_PRI, IO_ERR, IO_HUP, IO_NVAL};
+#[cfg(unix)]
+pub use self::source::{UnixFdTag, unix_fd_add, unix_fd_add_local, unix_fd_source_new};
+#[cfg(unix)]
+pub use self::source::{unix_signal_add, unix_signal_add_local, unix_signal_source_new};
+pub use self::source::{PRIORITY_HIGH, PRIORITY_DEFAULT, PRIORITY_HIGH_IDLE, PRIORITY_DEFAULT_IDLE, PRIORITY_LOW};
 pub use self::timeout_func::timeout;
 pub use self::traits::{FFIGObject, Connect};
-pub use self::value::{Value, ValuePublic};
+pub use self::value::{Value, ValuePublic, ToValue};
 pub use type_::Type;
 
 mod list;
@@ -31,7 +54,19 @@ mod slist;
 pub mod glib_container;
 mod error;
 mod permission;
+mod main_context;
+mod main_context_channel;
+mod main_context_futures;
+mod main_loop;
+mod closure;
+#[macro_use]
+mod object;
+pub mod param_spec;
+pub mod source;
+mod source_futures;
+mod thread_guard;
 pub mod signal;
+pub mod subclass;
 pub mod timeout_func;
 pub mod traits;
 pub mod translate;
@@ -51,34 +86,3 @@ pub fn to_bool(b: ffi::Gboolean) -> bool {
 
 // An opaque structure used as the base of all interface types.
 pub struct TypeInterface;
-
-// An opaque structure used as the base of all type instances.
-pub struct TypeInstance;
-
-// An opaque structure used as the base of all classes.
-pub struct TypeClass;
-
-//FIXME: Check if this is actually correct (maybe not since ParamFlags is deprecated)
-#[derive(Clone, Copy)]
-pub enum ParamFlags{
-    Readable,
-    Writable,
-    ReadWrite,
-    Construct,
-    ConstructOnly,
-    LaxValidation,
-    StaticName,
-    Private,
-    StaticNick,
-    StaticBlurb,
-    Deprecated
-}
-
-#[repr(C)]
-pub struct ParamSpec {
-    g_type_instance: TypeInstance,
-    name: *mut c_char,
-    flags: ParamFlags,
-    value_type: ffi::GType,
-    owner_type: ffi::GType,
-}
@@ -0,0 +1,478 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+//! GMainContext — A set of sources to be handled in a main loop
+
+use std::cell::RefCell;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ptr;
+use libc::c_int;
+use ffi::{self, gpointer};
+use source::{IOCondition, Priority, Source, SourceId, PRIORITY_DEFAULT};
+use translate::{FromGlibPtr, FromGlibPtrNotNull, Stash, ToGlib, ToGlibPtr, from_glib};
+
+/// A reference counted `GMainContext`
+///
+/// Cloning a `MainContext` adds a reference to the same context.
+#[derive(Debug)]
+pub struct MainContext {
+    pointer: *mut ffi::C_GMainContext,
+}
+
+unsafe impl Send for MainContext {}
+unsafe impl Sync for MainContext {}
+
+impl MainContext {
+    /// Creates a new context
+    pub fn new() -> MainContext {
+        unsafe { FromGlibPtrNotNull::take(ffi::g_main_context_new()) }
+    }
+
+    /// Returns the thread-default context of the calling thread
+    ///
+    /// Unlike `g_main_context_get_thread_default` this never returns `NULL`:
+    /// if no context was pushed for this thread the global default is returned.
+    pub fn thread_default() -> MainContext {
+        unsafe { FromGlibPtrNotNull::take(ffi::g_main_context_ref_thread_default()) }
+    }
+
+    /// Returns the context pushed as thread-default for the calling thread
+    /// or `None` if the global default is used
+    pub fn get_thread_default() -> Option<MainContext> {
+        unsafe { FromGlibPtr::borrow(ffi::g_main_context_get_thread_default()) }
+    }
+
+    /// Runs a single iteration of the context
+    ///
+    /// If `may_block` is `true` and no sources are ready, waits for a source
+    /// to become ready. Returns `true` if any events were dispatched.
+    pub fn iteration(&self, may_block: bool) -> bool {
+        unsafe { from_glib(ffi::g_main_context_iteration(self.pointer, may_block.to_glib())) }
+    }
+
+    /// Checks whether any sources have pending events
+    pub fn pending(&self) -> bool {
+        unsafe { from_glib(ffi::g_main_context_pending(self.pointer)) }
+    }
+
+    /// Wakes up the context if it is currently blocking in `iteration`
+    ///
+    /// Can be called from any thread.
+    pub fn wakeup(&self) {
+        unsafe { ffi::g_main_context_wakeup(self.pointer) }
+    }
+
+    /// Checks whether the calling thread is the owner of the context
+    pub fn is_owner(&self) -> bool {
+        unsafe { from_glib(ffi::g_main_context_is_owner(self.pointer)) }
+    }
+
+    /// Tries to become the owner of the context
+    ///
+    /// Returns `None` if another thread owns the context, otherwise the
+    /// ownership is released when the returned guard is dropped. Acquiring
+    /// is recursive, a thread that already owns the context can acquire
+    /// it again.
+    pub fn acquire(&self) -> Option<MainContextAcquireGuard> {
+        let acquired: bool = unsafe { from_glib(ffi::g_main_context_acquire(self.pointer)) };
+        if acquired {
+            Some(MainContextAcquireGuard { context: self, _marker: PhantomData })
+        }
+        else {
+            None
+        }
+    }
+
+    /// Finds the source with the given id in the context
+    pub fn find_source_by_id(&self, source_id: &SourceId) -> Option<Source> {
+        unsafe {
+            FromGlibPtr::borrow(ffi::g_main_context_find_source_by_id(self.pointer,
+                source_id.to_glib()))
+        }
+    }
+
+    /// Calls `func` on the thread that owns the context
+    ///
+    /// If the calling thread owns the context, or can acquire it because no
+    /// other thread does, `func` is called right away. Otherwise it is
+    /// called by the next iteration of the context, as an idle source.
+    pub fn invoke<F>(&self, func: F)
+    where F: FnOnce() + Send + 'static {
+        self.invoke_with_priority(PRIORITY_DEFAULT, func)
+    }
+
+    /// Same as `invoke` but with a custom priority for the idle source
+    pub fn invoke_with_priority<F>(&self, priority: Priority, func: F)
+    where F: FnOnce() + Send + 'static {
+        let func: Box<RefCell<Option<F>>> = Box::new(RefCell::new(Some(func)));
+        unsafe {
+            ffi::g_main_context_invoke_full(self.pointer, priority, invoke_trampoline::<F>,
+                Box::into_raw(func) as gpointer, invoke_destroy::<F>)
+        }
+    }
+
+    /// Replaces the function the context uses to poll file descriptors
+    ///
+    /// `None` restores the default, `g_poll`. The function has the same
+    /// contract as `poll(2)` and is called with the context unlocked.
+    ///
+    /// # Safety
+    ///
+    /// `func` is called with raw `GPollFD`s and must handle them like
+    /// `g_poll` would, from whichever thread iterates the context.
+    pub unsafe fn set_poll_func(&self, func: Option<ffi::GPollFunc>) {
+        ffi::g_main_context_set_poll_func(self.pointer, func)
+    }
+
+    /// Returns the function the context uses to poll file descriptors
+    ///
+    /// # Safety
+    ///
+    /// The function can only be called with valid `GPollFD`s, while it is
+    /// still in use by the context.
+    pub unsafe fn get_poll_func(&self) -> Option<ffi::GPollFunc> {
+        ffi::g_main_context_get_poll_func(self.pointer)
+    }
+
+    /// Makes the context the thread-default context of the calling thread
+    ///
+    /// The context is popped again when the returned guard is dropped, so
+    /// sources created by code that uses the thread-default context end up
+    /// attached to `self` for the lifetime of the guard.
+    pub fn with_thread_default(&self) -> ThreadDefaultGuard {
+        unsafe { ffi::g_main_context_push_thread_default(self.pointer) }
+        ThreadDefaultGuard { context: self, _marker: PhantomData }
+    }
+}
+
+impl Default for MainContext {
+    /// Returns the global default context, the one used by the main loop
+    /// if no context was given to it
+    fn default() -> MainContext {
+        unsafe { FromGlibPtrNotNull::borrow(ffi::g_main_context_default()) }
+    }
+}
+
+extern "C" fn invoke_trampoline<F: FnOnce() + Send + 'static>(func: gpointer) -> ffi::Gboolean {
+    unsafe {
+        let func = &*(func as *const RefCell<Option<F>>);
+        if let Some(func) = func.borrow_mut().take() {
+            func();
+        }
+    }
+    ffi::GFALSE
+}
+
+extern "C" fn invoke_destroy<F: FnOnce() + Send + 'static>(ptr: gpointer) {
+    unsafe {
+        drop(Box::from_raw(ptr as *mut RefCell<Option<F>>));
+    }
+}
+
+/// Ownership of a `MainContext`, released when dropped
+///
+/// Returned by `MainContext::acquire`.
+pub struct MainContextAcquireGuard<'a> {
+    context: &'a MainContext,
+    // the context must be released by the thread that acquired it
+    _marker: PhantomData<*mut ()>,
+}
+
+/// The steps of an iteration are exposed on the guard so the context can be
+/// driven by an external event loop:
+///
+/// ```ignore
+///     let acquire = context.acquire().unwrap();
+///     let mut fds = Vec::new();
+///     loop {
+///         let (_, max_priority) = acquire.prepare();
+///         let timeout = acquire.query(max_priority, &mut fds);
+///         // register `fds` with the external loop, wait for at most
+///         // `timeout` and fill in the received events with `set_revents`
+///         if acquire.check(max_priority, &mut fds) {
+///             acquire.dispatch();
+///         }
+///     }
+/// ```
+impl <'a> MainContextAcquireGuard<'a> {
+    /// Returns the acquired context
+    pub fn context(&self) -> &'a MainContext {
+        self.context
+    }
+
+    /// Prepares the sources of the context for polling
+    ///
+    /// Returns whether a source is ready to be dispatched before polling,
+    /// and the priority of the most important ready source, to be passed to
+    /// `query` and `check`.
+    pub fn prepare(&self) -> (bool, Priority) {
+        let mut priority = 0;
+        let ready = unsafe { ffi::g_main_context_prepare(self.context.pointer, &mut priority) };
+        (from_glib(ready), priority)
+    }
+
+    /// Fills `fds` with the file descriptors the context needs to poll for
+    /// sources of priority `max_priority` or higher
+    ///
+    /// Returns the maximum time to poll for in milliseconds, `None` places no
+    /// limit on the poll. The buffer can be reused across iterations.
+    pub fn query(&self, max_priority: Priority, fds: &mut Vec<PollFD>) -> Option<u32> {
+        let mut timeout = -1;
+        loop {
+            let needed = unsafe {
+                ffi::g_main_context_query(self.context.pointer, max_priority, &mut timeout,
+                    fds.as_mut_ptr() as *mut ffi::C_GPollFD, fds.len() as c_int) as usize
+            };
+            if needed <= fds.len() {
+                fds.truncate(needed);
+                break;
+            }
+            fds.resize(needed, PollFD::new(-1, IOCondition::empty()));
+        }
+        if timeout < 0 { None } else { Some(timeout as u32) }
+    }
+
+    /// Passes the results of polling `fds` back to the context
+    ///
+    /// Returns whether any sources are ready to be dispatched.
+    pub fn check(&self, max_priority: Priority, fds: &mut [PollFD]) -> bool {
+        unsafe {
+            from_glib(ffi::g_main_context_check(self.context.pointer, max_priority,
+                if fds.is_empty() { ptr::null_mut() } else { fds.as_mut_ptr() as *mut ffi::C_GPollFD },
+                fds.len() as c_int))
+        }
+    }
+
+    /// Dispatches all sources that were found ready by `check`
+    pub fn dispatch(&self) {
+        unsafe { ffi::g_main_context_dispatch(self.context.pointer) }
+    }
+}
+
+impl <'a> Drop for MainContextAcquireGuard<'a> {
+    fn drop(&mut self) {
+        unsafe { ffi::g_main_context_release(self.context.pointer) }
+    }
+}
+
+/// A thread-default `MainContext`, popped when dropped
+///
+/// Returned by `MainContext::with_thread_default`.
+pub struct ThreadDefaultGuard<'a> {
+    context: &'a MainContext,
+    // the context must be popped by the thread that pushed it
+    _marker: PhantomData<*mut ()>,
+}
+
+impl <'a> Drop for ThreadDefaultGuard<'a> {
+    fn drop(&mut self) {
+        unsafe { ffi::g_main_context_pop_thread_default(self.context.pointer) }
+    }
+}
+
+/// A file descriptor to be polled on behalf of a `MainContext`
+///
+/// Has the same layout as `GPollFD`.
+#[repr(C)]
+pub struct PollFD(ffi::C_GPollFD);
+
+impl PollFD {
+    pub fn new(fd: i32, events: IOCondition) -> PollFD {
+        PollFD(ffi::C_GPollFD { fd: fd as _, events: events.bits() as u16, revents: 0 })
+    }
+
+    /// Returns the file descriptor, or the handle on Windows
+    pub fn fd(&self) -> i32 {
+        // Windows handles only use the low 32 bits
+        self.0.fd as _
+    }
+
+    /// Returns the events to poll for
+    pub fn events(&self) -> IOCondition {
+        IOCondition::from_bits_truncate(self.0.events as u32)
+    }
+
+    /// Returns the events received by the last poll
+    pub fn revents(&self) -> IOCondition {
+        IOCondition::from_bits_truncate(self.0.revents as u32)
+    }
+
+    /// Sets the events received when polling
+    pub fn set_revents(&mut self, revents: IOCondition) {
+        self.0.revents = revents.bits() as u16;
+    }
+}
+
+impl Clone for PollFD {
+    fn clone(&self) -> PollFD {
+        PollFD(ffi::C_GPollFD { fd: self.0.fd, events: self.0.events, revents: self.0.revents })
+    }
+}
+
+impl Clone for MainContext {
+    fn clone(&self) -> MainContext {
+        unsafe { FromGlibPtrNotNull::borrow(self.pointer) }
+    }
+}
+
+impl Drop for MainContext {
+    fn drop(&mut self) {
+        unsafe { ffi::g_main_context_unref(self.pointer) }
+    }
+}
+
+impl PartialEq for MainContext {
+    fn eq(&self, other: &MainContext) -> bool {
+        self.pointer == other.pointer
+    }
+}
+
+impl Eq for MainContext {}
+
+impl Hash for MainContext {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pointer.hash(state)
+    }
+}
+
+impl <'a> ToGlibPtr<'a, *mut ffi::C_GMainContext> for MainContext {
+    type Storage = &'a MainContext;
+
+    #[inline]
+    fn borrow_to_glib(&'a self) -> Stash<*mut ffi::C_GMainContext, MainContext> {
+        Stash(self.pointer, self)
+    }
+}
+
+impl FromGlibPtrNotNull<*mut ffi::C_GMainContext> for MainContext {
+    unsafe fn borrow(ptr: *mut ffi::C_GMainContext) -> MainContext {
+        debug_assert!(!ptr.is_null());
+        MainContext { pointer: ffi::g_main_context_ref(ptr) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GMainContext) -> MainContext {
+        debug_assert!(!ptr.is_null());
+        MainContext { pointer: ptr }
+    }
+}
+
+impl FromGlibPtr<*mut ffi::C_GMainContext> for Option<MainContext> {
+    unsafe fn borrow(ptr: *mut ffi::C_GMainContext) -> Option<MainContext> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::borrow(ptr)) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GMainContext) -> Option<MainContext> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::take(ptr)) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MainContext;
+
+    #[test]
+    fn acquire() {
+        let context = MainContext::new();
+        assert!(!context.is_owner());
+        {
+            let _guard = context.acquire().unwrap();
+            assert!(context.is_owner());
+            assert!(!context.pending());
+            assert!(!context.iteration(false));
+        }
+        assert!(!context.is_owner());
+    }
+
+    #[test]
+    fn thread_default() {
+        let context = MainContext::new();
+        assert!(MainContext::get_thread_default().is_none());
+        {
+            let _guard = context.with_thread_default();
+            assert!(MainContext::get_thread_default() == Some(context.clone()));
+            assert!(MainContext::thread_default() == context);
+        }
+        assert!(MainContext::get_thread_default().is_none());
+        assert!(MainContext::thread_default() == MainContext::default());
+    }
+
+    #[test]
+    fn invoke_from_other_thread() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::thread;
+
+        let context = MainContext::new();
+        let _acquire = context.acquire().unwrap();
+        let called = Arc::new(AtomicBool::new(false));
+        let context_clone = context.clone();
+        let called_clone = called.clone();
+        thread::spawn(move || {
+            context_clone.invoke(move || called_clone.store(true, Ordering::SeqCst));
+        }).join().unwrap();
+
+        // queued since this thread owns the context
+        assert!(!called.load(Ordering::SeqCst));
+        while !called.load(Ordering::SeqCst) {
+            context.iteration(true);
+        }
+    }
+
+    #[test]
+    fn poll_func() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use libc::{c_int, c_uint};
+        use ffi;
+
+        static POLLS: AtomicUsize = AtomicUsize::new(0);
+
+        unsafe extern "C" fn counting_poll(fds: *mut ffi::C_GPollFD, nfds: c_uint, timeout: c_int) -> c_int {
+            POLLS.fetch_add(1, Ordering::SeqCst);
+            ffi::g_poll(fds, nfds, timeout)
+        }
+
+        let context = MainContext::new();
+        let _acquire = context.acquire().unwrap();
+        unsafe {
+            assert!(context.get_poll_func().is_some());
+            context.set_poll_func(Some(counting_poll));
+        }
+        context.iteration(false);
+        assert_eq!(POLLS.load(Ordering::SeqCst), 1);
+
+        unsafe { context.set_poll_func(None) };
+        context.iteration(false);
+        assert_eq!(POLLS.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn external_poll() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use source::{Continue, idle_source_new};
+
+        let context = MainContext::new();
+        let acquire = context.acquire().unwrap();
+        let dispatched = Arc::new(AtomicBool::new(false));
+        let dispatched_clone = dispatched.clone();
+        idle_source_new(move || {
+            dispatched_clone.store(true, Ordering::SeqCst);
+            Continue(false)
+        }).attach(&context);
+
+        let mut fds = Vec::new();
+        let (ready, max_priority) = acquire.prepare();
+        assert!(ready);
+        let timeout = acquire.query(max_priority, &mut fds);
+        assert_eq!(timeout, Some(0));
+        // the context always polls its wakeup fd
+        assert!(!fds.is_empty());
+        assert!(acquire.check(max_priority, &mut fds));
+        acquire.dispatch();
+        assert!(dispatched.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+//! A channel whose messages are received by a `MainContext`
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::SendError;
+use ffi;
+use main_context::MainContext;
+use source::{Continue, Priority, Source, SourceFuncs, SourceId, SourcePtr};
+use thread_guard::ThreadGuard;
+use translate::ToGlibPtr;
+
+struct ChannelState<T> {
+    queue: VecDeque<T>,
+    // not a reference, the source owns the channel and clears this when
+    // it's finalized
+    source: Option<SourcePtr>,
+    senders: usize,
+    receiver_gone: bool,
+}
+
+impl <T> ChannelState<T> {
+    fn wakeup(&self) {
+        if let Some(ref source) = self.source {
+            unsafe { ffi::g_source_set_ready_time(source.0, 0) }
+        }
+    }
+}
+
+type Channel<T> = Arc<Mutex<ChannelState<T>>>;
+
+/// The sending half of a channel created with `MainContext::channel`
+///
+/// Can be cloned, and sent to other threads if `T` is `Send`.
+pub struct Sender<T> {
+    channel: Channel<T>,
+}
+
+impl <T> Sender<T> {
+    /// Sends a message to the receiver
+    ///
+    /// Messages are received in the order they were sent. Returns the
+    /// message back if the receiver was dropped or its closure returned
+    /// `Continue(false)`.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let mut state = self.channel.lock().unwrap();
+        if state.receiver_gone {
+            return Err(SendError(value));
+        }
+        state.queue.push_back(value);
+        state.wakeup();
+        Ok(())
+    }
+}
+
+impl <T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.channel.lock().unwrap().senders += 1;
+        Sender { channel: self.channel.clone() }
+    }
+}
+
+impl <T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.channel.lock().unwrap();
+        state.senders -= 1;
+        if state.senders == 0 {
+            // let the source remove itself
+            state.wakeup();
+        }
+    }
+}
+
+/// The receiving half of a channel created with `MainContext::channel`
+///
+/// Messages sent before the receiver is attached are queued up.
+pub struct Receiver<T> {
+    channel: Option<Channel<T>>,
+    priority: Priority,
+}
+
+impl <T: Send + 'static> Receiver<T> {
+    /// Attaches the receiver to `context`, which calls `func` with each
+    /// message
+    ///
+    /// The source is removed once all senders are dropped and all messages
+    /// were received, or once `func` returns `Continue(false)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context is owned by another thread.
+    ///
+    /// The process is aborted if the context is later iterated from another
+    /// thread, since `func` can't be called or dropped there.
+    pub fn attach<F>(mut self, context: &MainContext, func: F) -> SourceId
+    where F: FnMut(T) -> Continue + 'static {
+        let _acquire = context.acquire().expect("The context is owned by another thread");
+        let channel = self.channel.take().unwrap();
+        let source = Source::new(ChannelSource {
+            channel: channel.clone(),
+            func: RefCell::new(ThreadGuard::new(func)),
+        });
+        source.set_priority(self.priority);
+        channel.lock().unwrap().source = Some(SourcePtr(source.borrow_to_glib().0));
+        source.set_ready_time(0);
+        source.attach(context)
+    }
+}
+
+impl <T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if let Some(ref channel) = self.channel {
+            let mut state = channel.lock().unwrap();
+            state.receiver_gone = true;
+            state.queue.clear();
+        }
+    }
+}
+
+struct ChannelSource<T, F> {
+    channel: Channel<T>,
+    func: RefCell<ThreadGuard<F>>,
+}
+
+impl <T: Send + 'static, F: FnMut(T) -> Continue + 'static> SourceFuncs for ChannelSource<T, F> {
+    fn dispatch(&self, source: &Source) -> Continue {
+        source.set_ready_time(-1);
+        // one message per dispatch so higher priority sources get a chance
+        // to run in between
+        let value = {
+            let mut state = self.channel.lock().unwrap();
+            match state.queue.pop_front() {
+                Some(value) => {
+                    if !state.queue.is_empty() || state.senders == 0 {
+                        source.set_ready_time(0);
+                    }
+                    value
+                }
+                None => return Continue(state.senders > 0),
+            }
+        };
+
+        let mut func = match self.func.try_borrow_mut() {
+            Ok(func) => func,
+            Err(_) => panic!("Channel receiver called recursively"),
+        };
+        let res = (func.get_mut())(value);
+        if !res.0 {
+            let mut state = self.channel.lock().unwrap();
+            state.receiver_gone = true;
+            state.queue.clear();
+        }
+        res
+    }
+
+    fn finalize(&mut self) {
+        let mut state = self.channel.lock().unwrap();
+        state.source = None;
+        state.receiver_gone = true;
+        state.queue.clear();
+    }
+}
+
+impl MainContext {
+    /// Creates a channel whose `Receiver` can be attached to a context
+    ///
+    /// Any number of threads can send messages through clones of the
+    /// `Sender`, they are handled in the order they were sent by the
+    /// thread iterating the context the receiver is attached to. The source
+    /// of the receiver gets `priority`.
+    pub fn channel<T>(priority: Priority) -> (Sender<T>, Receiver<T>) {
+        let channel = Arc::new(Mutex::new(ChannelState {
+            queue: VecDeque::new(),
+            source: None,
+            senders: 1,
+            receiver_gone: false,
+        }));
+        (Sender { channel: channel.clone() }, Receiver { channel: Some(channel), priority: priority })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::thread;
+    use main_context::MainContext;
+    use source::{Continue, PRIORITY_DEFAULT};
+
+    #[test]
+    fn messages_in_order() {
+        let context = MainContext::new();
+        let (sender, receiver) = MainContext::channel(PRIORITY_DEFAULT);
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let received_clone = received.clone();
+        receiver.attach(&context, move |value| {
+            received_clone.borrow_mut().push(value);
+            Continue(true)
+        });
+
+        let handles: Vec<_> = (0..2).map(|i| {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                for j in 0..10 {
+                    sender.send(i * 10 + j).unwrap();
+                }
+            })
+        }).collect();
+        drop(sender);
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let _acquire = context.acquire().unwrap();
+        while context.iteration(false) {}
+        let received = received.borrow();
+        assert_eq!(received.len(), 20);
+        for i in 0..2 {
+            let from_thread: Vec<_> = received.iter().cloned().filter(|v| v / 10 == i).collect();
+            assert_eq!(from_thread, (i * 10..i * 10 + 10).collect::<Vec<_>>());
+        }
+    }
+}
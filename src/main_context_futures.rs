@@ -0,0 +1,332 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+//! Running `std::future::Future`s on a `MainContext`
+
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
+use ffi;
+use main_context::MainContext;
+use source::{Continue, Source, SourceFuncs, SourcePtr, PRIORITY_DEFAULT};
+use thread_guard::ThreadGuard;
+use translate::ToGlibPtr;
+
+/// The error returned by a `JoinHandle` whose task was dropped before it
+/// completed
+///
+/// This happens if the task was aborted or its context was destroyed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Aborted;
+
+impl fmt::Display for Aborted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Task aborted")
+    }
+}
+
+// Shared between a task's source, its wakers and its `JoinHandle`. It
+// doesn't own a reference to the source so that a waker stored inside the
+// task's own future doesn't keep the source alive forever.
+struct TaskState {
+    source: Mutex<Option<SourcePtr>>,
+    aborted: AtomicBool,
+}
+
+impl Wake for TaskState {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        // The lock keeps the GSource allocated, finalize has to take it to
+        // clear `source` before the memory is freed. The source clears it
+        // too before it removes itself, so only a context destroyed with
+        // the task still pending can race: finalize may have started and be
+        // waiting for the lock, GLib then warns about the dead source and
+        // ignores the ready time.
+        if let Some(ref source) = *self.source.lock().unwrap() {
+            unsafe { ffi::g_source_set_ready_time(source.0, 0) }
+        }
+    }
+}
+
+enum FutureObj {
+    Send(Pin<Box<dyn Future<Output = ()> + Send>>),
+    Local(ThreadGuard<Pin<Box<dyn Future<Output = ()>>>>),
+}
+
+impl FutureObj {
+    fn poll(&mut self, cx: &mut Context) -> Poll<()> {
+        match *self {
+            FutureObj::Send(ref mut future) => future.as_mut().poll(cx),
+            FutureObj::Local(ref mut future) => future.get_mut().as_mut().poll(cx),
+        }
+    }
+}
+
+struct TaskSource {
+    future: RefCell<Option<FutureObj>>,
+    state: Arc<TaskState>,
+}
+
+impl SourceFuncs for TaskSource {
+    fn dispatch(&self, source: &Source) -> Continue {
+        source.set_ready_time(-1);
+        // a nested main loop run by the future can't poll it again
+        let mut future = match self.future.try_borrow_mut() {
+            Ok(future) => future,
+            Err(_) => return Continue(true),
+        };
+        if self.state.aborted.load(Ordering::SeqCst) {
+            *self.state.source.lock().unwrap() = None;
+            *future = None;
+            return Continue(false);
+        }
+
+        let done = match *future {
+            Some(ref mut obj) => {
+                let context = source.get_context().unwrap();
+                let _thread_default = context.with_thread_default();
+                let waker = Waker::from(self.state.clone());
+                let mut cx = Context::from_waker(&waker);
+                obj.poll(&mut cx).is_ready()
+            }
+            None => true,
+        };
+        if done {
+            *self.state.source.lock().unwrap() = None;
+            *future = None;
+        }
+        Continue(!done)
+    }
+
+    fn finalize(&mut self) {
+        *self.state.source.lock().unwrap() = None;
+    }
+}
+
+struct JoinInner<T> {
+    result: Option<Result<T, Aborted>>,
+    completed: bool,
+    waker: Option<Waker>,
+}
+
+impl <T> JoinInner<T> {
+    fn complete(&mut self, result: Result<T, Aborted>) {
+        if !self.completed {
+            self.completed = true;
+            self.result = Some(result);
+            if let Some(waker) = self.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+// Wraps the spawned future to hand its output to the `JoinHandle`, or an
+// error if it's dropped before completing.
+struct Task<F: Future> {
+    future: Pin<Box<F>>,
+    inner: Arc<Mutex<JoinInner<F::Output>>>,
+}
+
+impl <F: Future> Future for Task<F> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let this = &mut *self;
+        match this.future.as_mut().poll(cx) {
+            Poll::Ready(value) => {
+                this.inner.lock().unwrap().complete(Ok(value));
+                Poll::Ready(())
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl <F: Future> Drop for Task<F> {
+    fn drop(&mut self) {
+        self.inner.lock().unwrap().complete(Err(Aborted));
+    }
+}
+
+/// A handle to a task spawned on a `MainContext`
+///
+/// Awaiting the handle returns the output of the task. Dropping the handle
+/// detaches the task, which keeps running.
+pub struct JoinHandle<T> {
+    state: Arc<TaskState>,
+    inner: Arc<Mutex<JoinInner<T>>>,
+}
+
+impl <T> JoinHandle<T> {
+    /// Aborts the task
+    ///
+    /// The future of the task is dropped by its context the next time the
+    /// context is iterated, unless the task completes first. Awaiting the
+    /// handle then returns `Err(Aborted)`.
+    pub fn abort(&self) {
+        self.state.aborted.store(true, Ordering::SeqCst);
+        self.state.wake_by_ref();
+    }
+
+    /// Checks whether the task has completed or was dropped
+    pub fn is_finished(&self) -> bool {
+        self.inner.lock().unwrap().completed
+    }
+}
+
+impl <T> Future for JoinHandle<T> {
+    type Output = Result<T, Aborted>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T, Aborted>> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                assert!(!inner.completed, "JoinHandle polled after completion");
+                inner.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl MainContext {
+    /// Spawns a future on the context
+    ///
+    /// The future is polled by whichever thread iterates the context, with
+    /// the context pushed as its thread-default context.
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where F: Future + Send + 'static, F::Output: Send + 'static {
+        let (task, handle) = new_task(future);
+        self.spawn_obj(FutureObj::Send(Box::pin(task)), handle)
+    }
+
+    /// Spawns a future that doesn't need to be `Send` on the context
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context is owned by another thread.
+    ///
+    /// The process is aborted if the context is later iterated from another
+    /// thread, since the future can't be polled or dropped there.
+    pub fn spawn_local<F>(&self, future: F) -> JoinHandle<F::Output>
+    where F: Future + 'static, F::Output: 'static {
+        let _acquire = self.acquire().expect("The context is owned by another thread");
+        let (task, handle) = new_task(future);
+        let obj: Pin<Box<dyn Future<Output = ()>>> = Box::pin(task);
+        self.spawn_obj(FutureObj::Local(ThreadGuard::new(obj)), handle)
+    }
+
+    /// Runs the context until `future` completes and returns its output
+    ///
+    /// The context is acquired and pushed as the thread-default context for
+    /// the duration of the call, so futures spawned with `spawn_local` make
+    /// progress meanwhile.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the context is owned by another thread.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let _acquire = self.acquire().expect("The context is owned by another thread");
+        let _thread_default = self.with_thread_default();
+
+        let waker_state = Arc::new(BlockOnWaker {
+            context: self.clone(),
+            woken: AtomicBool::new(true),
+        });
+        let waker = Waker::from(waker_state.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if waker_state.woken.swap(false, Ordering::SeqCst) {
+                if let Poll::Ready(value) = future.as_mut().poll(&mut cx) {
+                    return value;
+                }
+            }
+            else {
+                self.iteration(true);
+            }
+        }
+    }
+
+    fn spawn_obj<T>(&self, obj: FutureObj, handle: JoinHandle<T>) -> JoinHandle<T> {
+        let source = Source::new(TaskSource {
+            future: RefCell::new(Some(obj)),
+            state: handle.state.clone(),
+        });
+        *handle.state.source.lock().unwrap() = Some(SourcePtr(source.borrow_to_glib().0));
+        source.set_priority(PRIORITY_DEFAULT);
+        source.set_ready_time(0);
+        source.attach(self);
+        handle
+    }
+}
+
+fn new_task<F: Future>(future: F) -> (Task<F>, JoinHandle<F::Output>) {
+    let inner = Arc::new(Mutex::new(JoinInner {
+        result: None,
+        completed: false,
+        waker: None,
+    }));
+    let state = Arc::new(TaskState {
+        source: Mutex::new(None),
+        aborted: AtomicBool::new(false),
+    });
+    let task = Task { future: Box::pin(future), inner: inner.clone() };
+    (task, JoinHandle { state: state, inner: inner })
+}
+
+struct BlockOnWaker {
+    context: MainContext,
+    woken: AtomicBool,
+}
+
+impl Wake for BlockOnWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref()
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::SeqCst);
+        self.context.wakeup();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future;
+    use std::rc::Rc;
+    use main_context::MainContext;
+    use super::Aborted;
+
+    #[test]
+    fn spawn() {
+        let context = MainContext::new();
+        let handle = context.spawn(future::ready(5));
+        assert_eq!(context.block_on(handle), Ok(5));
+    }
+
+    #[test]
+    fn spawn_local() {
+        let context = MainContext::new();
+        let handle = context.spawn_local(future::ready(Rc::new(3)));
+        assert_eq!(*context.block_on(handle).unwrap(), 3);
+    }
+
+    #[test]
+    fn abort() {
+        let context = MainContext::new();
+        let handle = context.spawn(future::pending::<()>());
+        handle.abort();
+        assert_eq!(context.block_on(handle), Err(Aborted));
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+//! GMainLoop — The main event loop
+
+use std::ptr;
+use ffi;
+use main_context::MainContext;
+use translate::{FromGlibPtr, FromGlibPtrNotNull, Stash, ToGlib, ToGlibPtr, from_glib};
+
+/// A reference counted `GMainLoop`
+///
+/// Cloning a `MainLoop` adds a reference to the same loop, so a clone can be
+/// moved into a callback to `quit` a loop that is being `run` elsewhere.
+pub struct MainLoop {
+    pointer: *mut ffi::C_GMainLoop,
+}
+
+unsafe impl Send for MainLoop {}
+unsafe impl Sync for MainLoop {}
+
+impl MainLoop {
+    /// Creates a new loop for `context` or for the global default context
+    /// if `None` is passed
+    ///
+    /// `is_running` only matters if the loop is run from a nested call,
+    /// it is usually `false`.
+    pub fn new(context: Option<&MainContext>, is_running: bool) -> MainLoop {
+        let context = match context {
+            Some(context) => context.borrow_to_glib().0,
+            None => ptr::null_mut(),
+        };
+        unsafe { FromGlibPtrNotNull::take(ffi::g_main_loop_new(context, is_running.to_glib())) }
+    }
+
+    /// Runs the loop until `quit` is called
+    ///
+    /// If this is called from the thread of the loop's context, the context
+    /// will be acquired for the duration of the call.
+    pub fn run(&self) {
+        unsafe { ffi::g_main_loop_run(self.pointer) }
+    }
+
+    /// Stops the loop from running
+    ///
+    /// Any calls to `run` for the loop will return after the current
+    /// iteration of the context is over.
+    pub fn quit(&self) {
+        unsafe { ffi::g_main_loop_quit(self.pointer) }
+    }
+
+    /// Checks whether the loop is currently being run
+    pub fn is_running(&self) -> bool {
+        unsafe { from_glib(ffi::g_main_loop_is_running(self.pointer)) }
+    }
+
+    /// Returns the context of the loop
+    pub fn context(&self) -> MainContext {
+        unsafe { FromGlibPtrNotNull::borrow(ffi::g_main_loop_get_context(self.pointer)) }
+    }
+}
+
+impl Clone for MainLoop {
+    fn clone(&self) -> MainLoop {
+        unsafe { FromGlibPtrNotNull::borrow(self.pointer) }
+    }
+}
+
+impl Drop for MainLoop {
+    fn drop(&mut self) {
+        unsafe { ffi::g_main_loop_unref(self.pointer) }
+    }
+}
+
+impl <'a> ToGlibPtr<'a, *mut ffi::C_GMainLoop> for MainLoop {
+    type Storage = &'a MainLoop;
+
+    #[inline]
+    fn borrow_to_glib(&'a self) -> Stash<*mut ffi::C_GMainLoop, MainLoop> {
+        Stash(self.pointer, self)
+    }
+}
+
+impl FromGlibPtrNotNull<*mut ffi::C_GMainLoop> for MainLoop {
+    unsafe fn borrow(ptr: *mut ffi::C_GMainLoop) -> MainLoop {
+        debug_assert!(!ptr.is_null());
+        MainLoop { pointer: ffi::g_main_loop_ref(ptr) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GMainLoop) -> MainLoop {
+        debug_assert!(!ptr.is_null());
+        MainLoop { pointer: ptr }
+    }
+}
+
+impl FromGlibPtr<*mut ffi::C_GMainLoop> for Option<MainLoop> {
+    unsafe fn borrow(ptr: *mut ffi::C_GMainLoop) -> Option<MainLoop> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::borrow(ptr)) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GMainLoop) -> Option<MainLoop> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::take(ptr)) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use main_context::MainContext;
+    use super::MainLoop;
+
+    #[test]
+    fn new_with_context() {
+        let context = MainContext::new();
+        let main_loop = MainLoop::new(Some(&context), false);
+        assert!(!main_loop.is_running());
+        assert!(main_loop.context() == context);
+        assert!(main_loop.clone().context() == context);
+    }
+}
@@ -0,0 +1,850 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+//! GObject — The base object type
+//!
+//! Wrappers of `GObject` subclasses and interfaces are newtypes around
+//! `Object` defined with `glib_object_wrapper!`. The class hierarchy is
+//! expressed with `IsA`, which `Cast` uses to check upcasts at compile time
+//! and to restrict downcasts to subclasses.
+
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+use std::mem::transmute;
+use std::ptr;
+use libc::c_ulong;
+use ffi;
+use closure::Closure;
+use error::{PropertyError, SignalError};
+use param_spec::{ParamSpec, PARAM_CONSTRUCT_ONLY, PARAM_READABLE, PARAM_WRITABLE};
+use signal::{self, SignalHandlerId};
+use traits::FFIGObject;
+use translate::{FromGlibPtr, FromGlibPtrNotNull, Stash, ToGlib, ToGlibPtr, from_glib, from_glib_full,
+    from_glib_none};
+use type_::{GetType, Type};
+use value::{ToValue, Value};
+
+/// A reference counted `GObject`
+///
+/// Cloning an `Object` adds a reference to the same object and dropping it
+/// removes one.
+#[derive(Debug)]
+pub struct Object {
+    pointer: *mut ffi::C_GObject,
+}
+
+impl Object {
+    /// Creates an object of type `type_` with construct properties
+    ///
+    /// The properties are validated against the class before the object is
+    /// created, construct-only properties are allowed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `type_` isn't derived from `Object` or can't be
+    /// instantiated.
+    pub fn new(type_: Type, properties: &[(&str, &dyn ToValue)]) -> Result<Object, PropertyError> {
+        assert!(type_.is_a(&Object::get_type()), "Type '{}' is not an object type", type_.name());
+        let names: Vec<&str> = properties.iter().map(|&(name, _)| name).collect();
+        let values: Vec<Value> = properties.iter().map(|&(_, value)| value.to_value()).collect();
+
+        unsafe {
+            let klass = ffi::g_type_class_ref(type_.to_glib()) as *mut ffi::C_GObjectClass;
+            let res = names.iter().zip(values.iter())
+                .map(|(name, value)| validate_property(klass, name, value, true))
+                .collect::<Result<Vec<()>, PropertyError>>();
+            ffi::g_type_class_unref(klass as ffi::gpointer);
+            res?;
+
+            let ptr = ffi::g_object_new_with_properties(type_.to_glib(), names.len() as u32,
+                names.borrow_to_glib().0, values.as_ptr() as *const ffi::C_GValue);
+            assert!(!ptr.is_null(), "Can't instantiate type '{}'", type_.name());
+            if from_glib(ffi::g_object_is_floating(ptr)) {
+                Ok(FromGlibPtrNotNull::sink(ptr))
+            }
+            else {
+                Ok(from_glib_full(ptr))
+            }
+        }
+    }
+
+    /// Returns the runtime type of the object
+    pub fn get_object_type(&self) -> Type {
+        unsafe { from_glib((*(*self.pointer).g_type_instance.g_class).g_type) }
+    }
+
+    fn get_class(&self) -> *mut ffi::C_GObjectClass {
+        unsafe { (*self.pointer).g_type_instance.g_class as *mut ffi::C_GObjectClass }
+    }
+}
+
+impl Clone for Object {
+    fn clone(&self) -> Object {
+        unsafe { from_glib_none(self.pointer) }
+    }
+}
+
+impl Drop for Object {
+    fn drop(&mut self) {
+        unsafe { ffi::g_object_unref(self.pointer) }
+    }
+}
+
+impl PartialEq for Object {
+    fn eq(&self, other: &Object) -> bool {
+        self.pointer == other.pointer
+    }
+}
+
+impl Eq for Object {}
+
+impl FFIGObject for Object {
+    fn unwrap_gobject(&self) -> *mut ffi::C_GObject {
+        self.pointer
+    }
+
+    unsafe fn wrap_object(object: *mut ffi::C_GObject) -> Object {
+        from_glib_none(object)
+    }
+}
+
+impl <'a> ToGlibPtr<'a, *mut ffi::C_GObject> for Object {
+    type Storage = &'a Object;
+
+    #[inline]
+    fn borrow_to_glib(&'a self) -> Stash<*mut ffi::C_GObject, Object> {
+        Stash(self.pointer, self)
+    }
+}
+
+impl FromGlibPtrNotNull<*mut ffi::C_GObject> for Object {
+    unsafe fn borrow(ptr: *mut ffi::C_GObject) -> Object {
+        debug_assert!(!ptr.is_null());
+        Object { pointer: ffi::g_object_ref(ptr) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GObject) -> Object {
+        debug_assert!(!ptr.is_null());
+        Object { pointer: ptr }
+    }
+
+    unsafe fn sink(ptr: *mut ffi::C_GObject) -> Object {
+        debug_assert!(!ptr.is_null());
+        Object { pointer: ffi::g_object_ref_sink(ptr) }
+    }
+}
+
+impl FromGlibPtr<*mut ffi::C_GObject> for Option<Object> {
+    unsafe fn borrow(ptr: *mut ffi::C_GObject) -> Option<Object> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::borrow(ptr)) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GObject) -> Option<Object> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::take(ptr)) }
+    }
+
+    unsafe fn sink(ptr: *mut ffi::C_GObject) -> Option<Object> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::sink(ptr)) }
+    }
+}
+
+impl GetType for Object {
+    fn get_type() -> Type {
+        unsafe { from_glib(ffi::g_object_get_type()) }
+    }
+}
+
+/// A wrapper of `GObject` or of a type derived from it
+///
+/// Implemented by `glib_object_wrapper!`, the wrapper must be a newtype
+/// around `Object` and `get_type` must return the wrapped `GType`.
+pub unsafe trait ObjectType: GetType + Clone + 'static {
+    /// Returns the wrapped `Object`
+    fn as_object(&self) -> &Object;
+
+    /// Unwraps the `Object`
+    fn into_object(self) -> Object;
+
+    /// Wraps `object` without checking its type
+    unsafe fn from_object_unchecked(object: Object) -> Self;
+}
+
+unsafe impl ObjectType for Object {
+    #[inline]
+    fn as_object(&self) -> &Object {
+        self
+    }
+
+    #[inline]
+    fn into_object(self) -> Object {
+        self
+    }
+
+    #[inline]
+    unsafe fn from_object_unchecked(object: Object) -> Object {
+        object
+    }
+}
+
+/// Declares that a wrapper type is `T`, a subclass of `T` or implements the
+/// interface `T`
+///
+/// Implementing it for types that aren't actually related breaks `upcast`.
+pub unsafe trait IsA<T: ObjectType>: ObjectType {}
+
+unsafe impl <T: ObjectType> IsA<T> for T {}
+
+/// Casts between object wrappers
+///
+/// Implemented for all `ObjectType`s.
+pub trait Cast: ObjectType {
+    /// Casts to a superclass or interface
+    ///
+    /// This is checked at compile time.
+    fn upcast<T: ObjectType>(self) -> T
+    where Self: IsA<T> {
+        unsafe { T::from_object_unchecked(self.into_object()) }
+    }
+
+    /// Casts to a subclass, returning `self` back if the object isn't of
+    /// type `T`
+    fn downcast<T: ObjectType>(self) -> Result<T, Self>
+    where T: IsA<Self> {
+        self.dynamic_cast()
+    }
+
+    /// Casts to any type, returning `self` back if the object isn't of type
+    /// `T`
+    ///
+    /// Unlike `downcast` this can be used for interfaces that aren't known to
+    /// be implemented at compile time.
+    fn dynamic_cast<T: ObjectType>(self) -> Result<T, Self> {
+        if self.is::<T>() {
+            Ok(unsafe { T::from_object_unchecked(self.into_object()) })
+        }
+        else {
+            Err(self)
+        }
+    }
+
+    /// Checks whether the object is of type `T`
+    fn is<T: ObjectType>(&self) -> bool {
+        self.as_object().get_object_type().is_a(&T::get_type())
+    }
+}
+
+impl <T: ObjectType> Cast for T {}
+
+unsafe fn find_property(klass: *mut ffi::C_GObjectClass, name: &str) -> Result<ParamSpec, PropertyError> {
+    let pspec: Option<ParamSpec> =
+        FromGlibPtr::borrow(ffi::g_object_class_find_property(klass, name.borrow_to_glib().0));
+    pspec.ok_or_else(|| PropertyError::NotFound(name.to_string()))
+}
+
+unsafe fn validate_property(klass: *mut ffi::C_GObjectClass, name: &str, value: &Value, constructing: bool)
+        -> Result<(), PropertyError> {
+    let pspec = find_property(klass, name)?;
+    let flags = pspec.get_flags();
+    if !flags.contains(PARAM_WRITABLE) {
+        return Err(PropertyError::NotWritable(name.to_string()));
+    }
+    if !constructing && flags.contains(PARAM_CONSTRUCT_ONLY) {
+        return Err(PropertyError::ConstructOnly(name.to_string()));
+    }
+    let expected = pspec.get_value_type();
+    let actual = value.value_type();
+    if !Value::compatible(actual, expected) && !Value::transformable(actual, expected) {
+        return Err(PropertyError::WrongType { name: name.to_string(), expected: expected, actual: actual });
+    }
+    Ok(())
+}
+
+/// Property access
+///
+/// Implemented for all `ObjectType`s. Names and values are checked against
+/// the `ParamSpec`s of the object's class before calling into GLib.
+pub trait ObjectExt: ObjectType {
+    /// Looks up the `ParamSpec` of the property `name`
+    fn find_property(&self, name: &str) -> Option<ParamSpec> {
+        unsafe { find_property(self.as_object().get_class(), name).ok() }
+    }
+
+    /// Returns the `ParamSpec`s of all properties of the object
+    fn list_properties(&self) -> Vec<ParamSpec> {
+        unsafe {
+            let mut n_properties = 0;
+            let pspecs = ffi::g_object_class_list_properties(self.as_object().get_class(), &mut n_properties);
+            let res = (0..n_properties as isize).map(|i| from_glib_none(*pspecs.offset(i))).collect();
+            ffi::g_free(pspecs as ffi::gpointer);
+            res
+        }
+    }
+
+    /// Sets the property `name` to `value`
+    fn set_property(&self, name: &str, value: &dyn ToValue) -> Result<(), PropertyError> {
+        self.set_properties(&[(name, value)])
+    }
+
+    /// Sets several properties at once
+    ///
+    /// Nothing is set unless all properties are valid. Notifications are
+    /// emitted after all properties were set.
+    fn set_properties(&self, properties: &[(&str, &dyn ToValue)]) -> Result<(), PropertyError> {
+        let object = self.as_object();
+        let values: Vec<Value> = properties.iter().map(|&(_, value)| value.to_value()).collect();
+        for (&(name, _), value) in properties.iter().zip(values.iter()) {
+            unsafe { validate_property(object.get_class(), name, value, false)? };
+        }
+
+        unsafe {
+            ffi::g_object_freeze_notify(object.pointer);
+            for (&(name, _), value) in properties.iter().zip(values.iter()) {
+                ffi::g_object_set_property(object.pointer, name.borrow_to_glib().0, value.as_ptr());
+            }
+            ffi::g_object_thaw_notify(object.pointer);
+        }
+        Ok(())
+    }
+
+    /// Returns the value of the property `name`
+    fn get_property(&self, name: &str) -> Result<Value, PropertyError> {
+        let object = self.as_object();
+        let pspec = unsafe { find_property(object.get_class(), name)? };
+        if !pspec.get_flags().contains(PARAM_READABLE) {
+            return Err(PropertyError::NotReadable(name.to_string()));
+        }
+        let mut value = Value::from_type(pspec.get_value_type());
+        unsafe { ffi::g_object_get_property(object.pointer, name.borrow_to_glib().0, value.as_mut_ptr()) }
+        Ok(value)
+    }
+
+    /// Connects `f` to the signal `name`
+    ///
+    /// `f` gets the object as the first value, followed by the signal
+    /// arguments, and returns the return value of the signal if it has one.
+    /// If `after` is `true`, `f` is called after the class handler. Fails
+    /// if the object has no signal `name`.
+    fn connect<F>(&self, name: &str, after: bool, f: F) -> Result<SignalHandlerId, SignalError>
+    where F: Fn(&[Value]) -> Option<Value> + 'static {
+        unsafe {
+            signal::connect_closure(self.as_object().pointer as ffi::gpointer, name, &Closure::new(f), after)
+        }
+    }
+
+    /// Connects to the `notify` signal, which is emitted after the property
+    /// `name` changed or after any property changed if `None` is passed
+    fn connect_notify<F>(&self, name: Option<&str>, f: F) -> SignalHandlerId
+    where F: Fn(&Object, &ParamSpec) + 'static {
+        let signal_name = match name {
+            Some(name) => format!("notify::{}", name),
+            None => "notify".to_string(),
+        };
+        unsafe {
+            let f: Box<Box<dyn Fn(&Object, &ParamSpec) + 'static>> = Box::new(Box::new(f));
+            // Every object has `notify`, and any detail parses whether or
+            // not the property exists
+            signal::connect_unchecked(self.as_object().pointer as ffi::gpointer, &signal_name,
+                transmute::<NotifyTrampoline, ffi::GCallback>(notify_trampoline), Box::into_raw(f))
+        }
+    }
+
+    /// Disconnects the signal handler `id`
+    ///
+    /// The closure of the handler is dropped.
+    fn disconnect(&self, id: SignalHandlerId) {
+        unsafe { ffi::g_signal_handler_disconnect(self.as_object().pointer as ffi::gpointer, id.to_glib()) }
+    }
+
+    /// Blocks the signal handler `id` until `unblock_signal` is called
+    ///
+    /// Calls nest, the handler stays blocked until it was unblocked as
+    /// often as it was blocked.
+    fn block_signal(&self, id: &SignalHandlerId) {
+        unsafe { ffi::g_signal_handler_block(self.as_object().pointer as ffi::gpointer, id.to_glib()) }
+    }
+
+    /// Unblocks the signal handler `id`
+    fn unblock_signal(&self, id: &SignalHandlerId) {
+        unsafe { ffi::g_signal_handler_unblock(self.as_object().pointer as ffi::gpointer, id.to_glib()) }
+    }
+
+    /// Blocks the signal handler `id` until the returned guard is dropped
+    fn block_signal_guard(&self, id: &SignalHandlerId) -> SignalBlockGuard {
+        self.block_signal(id);
+        SignalBlockGuard { object: self.as_object().clone(), handler: id.to_glib() }
+    }
+
+    /// Checks whether the signal handler `id` is still connected
+    fn is_signal_handler_connected(&self, id: &SignalHandlerId) -> bool {
+        unsafe {
+            from_glib(ffi::g_signal_handler_is_connected(self.as_object().pointer as ffi::gpointer,
+                id.to_glib()))
+        }
+    }
+
+    /// Emits the signal `name`, which can include a `::detail`
+    ///
+    /// Returns the return value of the signal if it has one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the object has no signal `name` or if the arguments don't
+    /// match the parameters of the signal.
+    fn emit_by_name(&self, name: &str, args: &[&dyn ToValue]) -> Option<Value> {
+        let type_ = self.as_object().get_object_type();
+        match signal::parse_name(name, type_, true) {
+            Some((signal_id, detail)) => self.emit(signal_id, detail, args),
+            None => panic!("Signal '{}' not found on type '{}'", name, type_.name()),
+        }
+    }
+
+    /// Emits the signal `signal_id` with the detail quark `detail`, which is
+    /// 0 for no detail
+    ///
+    /// Returns the return value of the signal if it has one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the signal isn't defined for the type of the object or if
+    /// the arguments don't match the parameters of the signal.
+    fn emit(&self, signal_id: u32, detail: u32, args: &[&dyn ToValue]) -> Option<Value> {
+        let object = self.as_object();
+        let query = signal::query(signal_id).expect("Invalid signal id");
+        let signal_name = query.get_signal_name();
+        assert!(object.get_object_type().is_a(&query.get_owner_type()),
+            "Signal '{}' of type '{}' can't be emitted on type '{}'", signal_name,
+            query.get_owner_type().name(), object.get_object_type().name());
+        let param_types = query.get_param_types();
+        assert!(args.len() == param_types.len(),
+            "Signal '{}' takes {} arguments, {} given", signal_name, param_types.len(), args.len());
+
+        let mut values = Vec::with_capacity(args.len() + 1);
+        values.push(object.to_value());
+        for (i, (arg, &param_type)) in args.iter().zip(param_types).enumerate() {
+            let arg = arg.to_value();
+            if Value::compatible(arg.value_type(), param_type) {
+                values.push(arg);
+                continue;
+            }
+            let mut value = Value::from_type(param_type);
+            let transformed: bool = unsafe { from_glib(ffi::g_value_transform(arg.as_ptr(), value.as_mut_ptr())) };
+            assert!(transformed, "Argument {} of signal '{}' has type '{}', expected '{}'", i, signal_name,
+                arg.value_type().name(), param_type.name());
+            values.push(value);
+        }
+
+        let return_type = query.get_return_type();
+        let mut ret = if return_type == Type::Unit { None } else { Some(Value::from_type(return_type)) };
+        unsafe {
+            let ret_ptr = match ret {
+                Some(ref mut ret) => ret.as_mut_ptr(),
+                None => ptr::null_mut(),
+            };
+            ffi::g_signal_emitv(values.as_ptr() as *const ffi::C_GValue, signal_id, detail, ret_ptr);
+        }
+        ret
+    }
+
+    /// Emits `notify` for the property `name`
+    fn notify(&self, name: &str) {
+        unsafe { ffi::g_object_notify(self.as_object().pointer, name.borrow_to_glib().0) }
+    }
+
+    /// Emits `notify` for the property described by `pspec`
+    fn notify_by_pspec(&self, pspec: &ParamSpec) {
+        unsafe { ffi::g_object_notify_by_pspec(self.as_object().pointer, pspec.borrow_to_glib().0) }
+    }
+
+    /// Creates a weak reference to the object
+    fn downgrade(&self) -> WeakRef<Self> {
+        let weak_ref = WeakRef::new();
+        unsafe { ffi::g_weak_ref_set(weak_ref.inner.get(), self.as_object().pointer) }
+        weak_ref
+    }
+
+    /// Calls `f` when the object is finalized
+    ///
+    /// `f` can't access the object anymore. It's leaked if the object is
+    /// never finalized.
+    fn add_weak_ref_notify<F: FnOnce() + 'static>(&self, f: F) {
+        let f = Box::into_raw(Box::new(f));
+        unsafe {
+            ffi::g_object_weak_ref(self.as_object().pointer, weak_notify_trampoline::<F>, f as ffi::gpointer)
+        }
+    }
+
+    /// Stops emitting `notify` until the returned guard is dropped
+    ///
+    /// Notifications are queued up meanwhile and emitted once per property
+    /// when the last guard of the object is dropped.
+    fn freeze_notify(&self) -> NotifyFreezeGuard {
+        let object = self.as_object().clone();
+        unsafe { ffi::g_object_freeze_notify(object.pointer) }
+        NotifyFreezeGuard { object: object }
+    }
+}
+
+impl <T: ObjectType> ObjectExt for T {}
+
+type NotifyTrampoline = extern "C" fn(*mut ffi::C_GObject, *mut ffi::C_GParamSpec,
+                                     &Box<dyn Fn(&Object, &ParamSpec) + 'static>);
+
+extern "C" fn notify_trampoline(this: *mut ffi::C_GObject, pspec: *mut ffi::C_GParamSpec,
+                                f: &Box<dyn Fn(&Object, &ParamSpec) + 'static>) {
+    unsafe { f(&from_glib_none(this), &from_glib_none(pspec)) }
+}
+
+extern "C" fn weak_notify_trampoline<F: FnOnce() + 'static>(data: ffi::gpointer, _: *mut ffi::C_GObject) {
+    let f = unsafe { Box::from_raw(data as *mut F) };
+    f()
+}
+
+/// A weak reference to an object
+///
+/// It doesn't keep the object alive, so it can be captured by closures
+/// connected to signals of the object without creating a reference cycle.
+pub struct WeakRef<T: ObjectType> {
+    // GLib keeps track of the address of the GWeakRef and clears it when the
+    // object is finalized
+    inner: Box<UnsafeCell<ffi::C_GWeakRef>>,
+    phantom: PhantomData<*const T>,
+}
+
+impl <T: ObjectType> WeakRef<T> {
+    /// Creates a weak reference that doesn't point to an object
+    pub fn new() -> WeakRef<T> {
+        let inner = Box::new(UnsafeCell::new(ffi::C_GWeakRef { priv_: ptr::null_mut() }));
+        unsafe { ffi::g_weak_ref_init(inner.get(), ptr::null_mut()) }
+        WeakRef { inner: inner, phantom: PhantomData }
+    }
+
+    /// Returns a strong reference to the object, or `None` if it was
+    /// finalized
+    pub fn upgrade(&self) -> Option<T> {
+        unsafe {
+            let ptr = ffi::g_weak_ref_get(self.inner.get());
+            let object: Option<Object> = FromGlibPtr::take(ptr);
+            object.map(|object| T::from_object_unchecked(object))
+        }
+    }
+}
+
+impl <T: ObjectType> Default for WeakRef<T> {
+    fn default() -> WeakRef<T> {
+        WeakRef::new()
+    }
+}
+
+impl <T: ObjectType> Clone for WeakRef<T> {
+    fn clone(&self) -> WeakRef<T> {
+        let weak_ref = WeakRef::new();
+        if let Some(object) = self.upgrade() {
+            unsafe { ffi::g_weak_ref_set(weak_ref.inner.get(), object.as_object().pointer) }
+        }
+        weak_ref
+    }
+}
+
+impl <T: ObjectType> Drop for WeakRef<T> {
+    fn drop(&mut self) {
+        unsafe { ffi::g_weak_ref_clear(self.inner.get()) }
+    }
+}
+
+/// Unblocks a signal handler when dropped
+///
+/// Returned by `ObjectExt::block_signal_guard`.
+pub struct SignalBlockGuard {
+    object: Object,
+    handler: c_ulong,
+}
+
+impl Drop for SignalBlockGuard {
+    fn drop(&mut self) {
+        unsafe {
+            let instance = self.object.pointer as ffi::gpointer;
+            // the handler may have been disconnected meanwhile
+            if from_glib(ffi::g_signal_handler_is_connected(instance, self.handler)) {
+                ffi::g_signal_handler_unblock(instance, self.handler);
+            }
+        }
+    }
+}
+
+/// Thaws `notify` of an object when dropped
+///
+/// Returned by `ObjectExt::freeze_notify`.
+pub struct NotifyFreezeGuard {
+    object: Object,
+}
+
+impl Drop for NotifyFreezeGuard {
+    fn drop(&mut self) {
+        unsafe { ffi::g_object_thaw_notify(self.object.pointer) }
+    }
+}
+
+/// Defines a wrapper type of a `GObject` subclass or interface
+///
+/// ```ignore
+/// glib_object_wrapper! {
+///     /// A widget that emits a signal when clicked on
+///     pub struct Button;
+///     get_type => ffi::gtk_button_get_type;
+///     implements => [Bin, Container, Widget, Buildable];
+/// }
+/// ```
+///
+/// `implements` lists all superclasses and interfaces other than `Object`.
+#[macro_export]
+macro_rules! glib_object_wrapper {
+    ($(#[$attr:meta])* pub struct $name:ident; get_type => $get_type:expr;
+     implements => [$($implements:ty),*];) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        pub struct $name($crate::Object);
+
+        unsafe impl $crate::ObjectType for $name {
+            #[inline]
+            fn as_object(&self) -> &$crate::Object {
+                &self.0
+            }
+
+            #[inline]
+            fn into_object(self) -> $crate::Object {
+                self.0
+            }
+
+            #[inline]
+            unsafe fn from_object_unchecked(object: $crate::Object) -> $name {
+                $name(object)
+            }
+        }
+
+        impl $crate::type_::GetType for $name {
+            fn get_type() -> $crate::Type {
+                unsafe { $crate::translate::from_glib(($get_type)()) }
+            }
+        }
+
+        unsafe impl $crate::IsA<$crate::Object> for $name {}
+        $(unsafe impl $crate::IsA<$implements> for $name {})*
+
+        impl $crate::ToValue for $name {
+            fn to_value(&self) -> $crate::Value {
+                $crate::ToValue::to_value(&self.0)
+            }
+        }
+
+        impl $crate::traits::FFIGObject for $name {
+            fn unwrap_gobject(&self) -> *mut $crate::ffi::C_GObject {
+                $crate::traits::FFIGObject::unwrap_gobject(&self.0)
+            }
+
+            unsafe fn wrap_object(object: *mut $crate::ffi::C_GObject) -> $name {
+                $crate::translate::from_glib_none(object)
+            }
+        }
+
+        impl <'a> $crate::translate::ToGlibPtr<'a, *mut $crate::ffi::C_GObject> for $name {
+            type Storage = &'a $crate::Object;
+
+            #[inline]
+            fn borrow_to_glib(&'a self) -> $crate::translate::Stash<*mut $crate::ffi::C_GObject, $name> {
+                let stash: $crate::translate::Stash<*mut $crate::ffi::C_GObject, $crate::Object> =
+                    $crate::translate::ToGlibPtr::borrow_to_glib(&self.0);
+                $crate::translate::Stash(stash.0, stash.1)
+            }
+        }
+
+        impl $crate::translate::FromGlibPtrNotNull<*mut $crate::ffi::C_GObject> for $name {
+            unsafe fn borrow(ptr: *mut $crate::ffi::C_GObject) -> $name {
+                let object: $crate::Object = $crate::translate::FromGlibPtrNotNull::borrow(ptr);
+                debug_assert!($crate::Cast::is::<$name>(&object));
+                $name(object)
+            }
+
+            unsafe fn take(ptr: *mut $crate::ffi::C_GObject) -> $name {
+                let object: $crate::Object = $crate::translate::FromGlibPtrNotNull::take(ptr);
+                debug_assert!($crate::Cast::is::<$name>(&object));
+                $name(object)
+            }
+
+            unsafe fn sink(ptr: *mut $crate::ffi::C_GObject) -> $name {
+                let object: $crate::Object = $crate::translate::FromGlibPtrNotNull::sink(ptr);
+                debug_assert!($crate::Cast::is::<$name>(&object));
+                $name(object)
+            }
+        }
+
+        impl $crate::translate::FromGlibPtr<*mut $crate::ffi::C_GObject> for Option<$name> {
+            unsafe fn borrow(ptr: *mut $crate::ffi::C_GObject) -> Option<$name> {
+                if ptr.is_null() { None }
+                else { Some($crate::translate::FromGlibPtrNotNull::borrow(ptr)) }
+            }
+
+            unsafe fn take(ptr: *mut $crate::ffi::C_GObject) -> Option<$name> {
+                if ptr.is_null() { None }
+                else { Some($crate::translate::FromGlibPtrNotNull::take(ptr)) }
+            }
+
+            unsafe fn sink(ptr: *mut $crate::ffi::C_GObject) -> Option<$name> {
+                if ptr.is_null() { None }
+                else { Some($crate::translate::FromGlibPtrNotNull::sink(ptr)) }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::ptr;
+    use std::rc::Rc;
+    use ffi;
+    use param_spec::PARAM_READWRITE;
+    use subclass::{ObjectClass, ObjectImpl, TypeData, register_type};
+    use translate::{ToGlib, from_glib_full};
+    use type_::GetType;
+    use super::*;
+
+    glib_object_wrapper! {
+        pub struct InitiallyUnowned;
+        get_type => ffi::g_initially_unowned_get_type;
+        implements => [];
+    }
+
+    fn new_object(type_: ::Type) -> Object {
+        unsafe { from_glib_full(ffi::g_object_new(type_.to_glib(), ptr::null())) }
+    }
+
+    #[test]
+    fn clone_adds_reference() {
+        let object = new_object(Object::get_type());
+        let pointer = object.unwrap_gobject();
+        let clone = object.clone();
+        assert!(clone == object);
+        assert_eq!(unsafe { (*pointer).ref_count }, 2);
+        drop(clone);
+        assert_eq!(unsafe { (*pointer).ref_count }, 1);
+    }
+
+    #[test]
+    fn wrap_object_adds_reference() {
+        let object = new_object(Object::get_type());
+        let pointer = object.unwrap_gobject();
+        let wrapped = unsafe { Object::wrap_object(pointer) };
+        assert!(wrapped == object);
+        assert_eq!(unsafe { (*pointer).ref_count }, 2);
+        drop(object);
+        assert_eq!(unsafe { (*pointer).ref_count }, 1);
+    }
+
+    #[test]
+    fn casts() {
+        let object = new_object(Object::get_type());
+        assert!(!object.is::<InitiallyUnowned>());
+        let object = object.downcast::<InitiallyUnowned>().unwrap_err();
+
+        let unowned = new_object(InitiallyUnowned::get_type()).downcast::<InitiallyUnowned>().unwrap();
+        assert_eq!(unowned.as_object().get_object_type(), InitiallyUnowned::get_type());
+        let upcast: Object = unowned.clone().upcast();
+        assert!(upcast == *unowned.as_object());
+        assert!(upcast.dynamic_cast::<InitiallyUnowned>().is_ok());
+        assert!(object.dynamic_cast::<InitiallyUnowned>().is_err());
+    }
+
+    #[test]
+    fn unknown_properties() {
+        let res = Object::new(Object::get_type(), &[("missing", &1)]);
+        assert_eq!(res.unwrap_err(), ::PropertyError::NotFound("missing".to_string()));
+
+        let object = Object::new(Object::get_type(), &[]).unwrap();
+        assert!(object.list_properties().is_empty());
+        assert!(object.find_property("missing").is_none());
+        assert!(object.set_property("missing", &"value").is_err());
+        assert!(object.get_property("missing").is_err());
+    }
+
+    struct Item;
+
+    unsafe impl ObjectImpl for Item {
+        fn type_name() -> &'static str {
+            "RsTestItem"
+        }
+
+        fn type_data() -> &'static TypeData {
+            static DATA: TypeData = TypeData::new();
+            &DATA
+        }
+
+        fn new() -> Item {
+            Item
+        }
+
+        fn class_init(klass: &mut ObjectClass) {
+            klass.install_property(1, ParamSpec::int("value", "Value", "A value", 0, 10, 0, PARAM_READWRITE));
+        }
+    }
+
+    #[test]
+    fn notify() {
+        let item = Object::new(register_type::<Item>(), &[]).unwrap();
+        let names = Rc::new(RefCell::new(Vec::new()));
+        let names_clone = names.clone();
+        item.connect_notify(None, move |object, pspec| {
+            assert_eq!(object.get_object_type().name(), "RsTestItem");
+            names_clone.borrow_mut().push(pspec.get_name());
+        });
+        let count = Rc::new(Cell::new(0));
+        let count_clone = count.clone();
+        item.connect_notify(Some("value"), move |_, _| count_clone.set(count_clone.get() + 1));
+
+        item.notify("value");
+        assert_eq!(*names.borrow(), ["value"]);
+        item.notify_by_pspec(&item.find_property("value").unwrap());
+        assert_eq!(count.get(), 2);
+
+        {
+            let _outer = item.freeze_notify();
+            let inner = item.freeze_notify();
+            item.notify("value");
+            item.notify("value");
+            drop(inner);
+            assert_eq!(count.get(), 2);
+        }
+        // queued up notifications are emitted once
+        assert_eq!(count.get(), 3);
+        assert_eq!(names.borrow().len(), 3);
+    }
+
+    #[test]
+    fn connect_unknown_signal() {
+        let object = Object::new(Object::get_type(), &[]).unwrap();
+        match object.connect("no-such-signal", false, |_| None) {
+            Err(SignalError::NotFound { ref name, .. }) if name == "no-such-signal" => (),
+            _ => panic!("Expected an unknown signal"),
+        }
+        assert!(object.connect("notify::anything", false, |_| None).is_ok());
+    }
+
+    #[test]
+    fn weak_ref() {
+        let object = Object::new(Object::get_type(), &[]).unwrap();
+        let finalized = Rc::new(Cell::new(false));
+        let finalized_clone = finalized.clone();
+        object.add_weak_ref_notify(move || finalized_clone.set(true));
+
+        let weak_ref = object.downgrade();
+        assert!(weak_ref.upgrade() == Some(object.clone()));
+        let weak_clone = weak_ref.clone();
+        drop(object);
+        assert!(finalized.get());
+        assert!(weak_ref.upgrade().is_none());
+        assert!(weak_clone.upgrade().is_none());
+    }
+}
@@ -0,0 +1,223 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+//! GParamSpec — Metadata of object properties
+
+use ffi;
+use translate::{FromGlib, FromGlibPtr, FromGlibPtrNotNull, Stash, ToGlib, ToGlibPtr, from_glib};
+use type_::Type;
+
+bitflags! {
+    /// Flags of a `ParamSpec`
+    flags ParamFlags: u32 {
+        /// The property can be read
+        const PARAM_READABLE = ffi::G_PARAM_READABLE,
+        /// The property can be written
+        const PARAM_WRITABLE = ffi::G_PARAM_WRITABLE,
+        /// The property can be read and written
+        const PARAM_READWRITE = ffi::G_PARAM_READWRITE,
+        /// The property is set when the object is constructed
+        const PARAM_CONSTRUCT = ffi::G_PARAM_CONSTRUCT,
+        /// The property can only be set when the object is constructed
+        const PARAM_CONSTRUCT_ONLY = ffi::G_PARAM_CONSTRUCT_ONLY,
+        /// Strict validation isn't required when setting the property
+        const PARAM_LAX_VALIDATION = ffi::G_PARAM_LAX_VALIDATION,
+        /// The name string is never freed
+        const PARAM_STATIC_NAME = ffi::G_PARAM_STATIC_NAME,
+        /// The nick string is never freed
+        const PARAM_STATIC_NICK = ffi::G_PARAM_STATIC_NICK,
+        /// The blurb string is never freed
+        const PARAM_STATIC_BLURB = ffi::G_PARAM_STATIC_BLURB,
+        /// `notify` is only emitted when explicitly requested
+        const PARAM_EXPLICIT_NOTIFY = ffi::G_PARAM_EXPLICIT_NOTIFY,
+        /// The property is deprecated
+        const PARAM_DEPRECATED = ffi::G_PARAM_DEPRECATED,
+    }
+}
+
+impl ToGlib for ParamFlags {
+    type GlibType = ffi::GParamFlags;
+
+    #[inline]
+    fn to_glib(&self) -> ffi::GParamFlags {
+        self.bits()
+    }
+}
+
+impl FromGlib<ffi::GParamFlags> for ParamFlags {
+    #[inline]
+    fn from_glib(value: ffi::GParamFlags) -> ParamFlags {
+        ParamFlags::from_bits_truncate(value)
+    }
+}
+
+/// A reference counted `GParamSpec` describing an object property
+pub struct ParamSpec {
+    pointer: *mut ffi::C_GParamSpec,
+}
+
+impl ParamSpec {
+    /// Creates a property of type `bool`
+    pub fn boolean(name: &str, nick: &str, blurb: &str, default_value: bool, flags: ParamFlags) -> ParamSpec {
+        unsafe {
+            FromGlibPtrNotNull::sink(ffi::g_param_spec_boolean(name.borrow_to_glib().0, nick.borrow_to_glib().0,
+                blurb.borrow_to_glib().0, default_value.to_glib(), flags.to_glib()))
+        }
+    }
+
+    /// Creates a property of type `i32`
+    pub fn int(name: &str, nick: &str, blurb: &str, minimum: i32, maximum: i32, default_value: i32,
+               flags: ParamFlags) -> ParamSpec {
+        unsafe {
+            FromGlibPtrNotNull::sink(ffi::g_param_spec_int(name.borrow_to_glib().0, nick.borrow_to_glib().0,
+                blurb.borrow_to_glib().0, minimum, maximum, default_value, flags.to_glib()))
+        }
+    }
+
+    /// Creates a property of type `u32`
+    pub fn uint(name: &str, nick: &str, blurb: &str, minimum: u32, maximum: u32, default_value: u32,
+                flags: ParamFlags) -> ParamSpec {
+        unsafe {
+            FromGlibPtrNotNull::sink(ffi::g_param_spec_uint(name.borrow_to_glib().0, nick.borrow_to_glib().0,
+                blurb.borrow_to_glib().0, minimum, maximum, default_value, flags.to_glib()))
+        }
+    }
+
+    /// Creates a property of type `i64`
+    pub fn int64(name: &str, nick: &str, blurb: &str, minimum: i64, maximum: i64, default_value: i64,
+                 flags: ParamFlags) -> ParamSpec {
+        unsafe {
+            FromGlibPtrNotNull::sink(ffi::g_param_spec_int64(name.borrow_to_glib().0, nick.borrow_to_glib().0,
+                blurb.borrow_to_glib().0, minimum, maximum, default_value, flags.to_glib()))
+        }
+    }
+
+    /// Creates a property of type `u64`
+    pub fn uint64(name: &str, nick: &str, blurb: &str, minimum: u64, maximum: u64, default_value: u64,
+                  flags: ParamFlags) -> ParamSpec {
+        unsafe {
+            FromGlibPtrNotNull::sink(ffi::g_param_spec_uint64(name.borrow_to_glib().0, nick.borrow_to_glib().0,
+                blurb.borrow_to_glib().0, minimum, maximum, default_value, flags.to_glib()))
+        }
+    }
+
+    /// Creates a property of type `f64`
+    pub fn double(name: &str, nick: &str, blurb: &str, minimum: f64, maximum: f64, default_value: f64,
+                  flags: ParamFlags) -> ParamSpec {
+        unsafe {
+            FromGlibPtrNotNull::sink(ffi::g_param_spec_double(name.borrow_to_glib().0, nick.borrow_to_glib().0,
+                blurb.borrow_to_glib().0, minimum, maximum, default_value, flags.to_glib()))
+        }
+    }
+
+    /// Creates a property of type `String`
+    pub fn string(name: &str, nick: &str, blurb: &str, default_value: Option<&str>,
+                  flags: ParamFlags) -> ParamSpec {
+        unsafe {
+            FromGlibPtrNotNull::sink(ffi::g_param_spec_string(name.borrow_to_glib().0, nick.borrow_to_glib().0,
+                blurb.borrow_to_glib().0, default_value.borrow_to_glib().0, flags.to_glib()))
+        }
+    }
+
+    /// Creates a property holding objects of type `object_type`
+    pub fn object(name: &str, nick: &str, blurb: &str, object_type: Type, flags: ParamFlags) -> ParamSpec {
+        unsafe {
+            FromGlibPtrNotNull::sink(ffi::g_param_spec_object(name.borrow_to_glib().0, nick.borrow_to_glib().0,
+                blurb.borrow_to_glib().0, object_type.to_glib(), flags.to_glib()))
+        }
+    }
+
+    /// Returns the canonical name of the property
+    pub fn get_name(&self) -> String {
+        unsafe { FromGlibPtrNotNull::borrow(ffi::g_param_spec_get_name(self.pointer)) }
+    }
+
+    /// Returns the nickname of the property
+    pub fn get_nick(&self) -> String {
+        unsafe { FromGlibPtrNotNull::borrow(ffi::g_param_spec_get_nick(self.pointer)) }
+    }
+
+    /// Returns the short description of the property
+    pub fn get_blurb(&self) -> Option<String> {
+        unsafe { FromGlibPtr::borrow(ffi::g_param_spec_get_blurb(self.pointer)) }
+    }
+
+    /// Returns the flags of the property
+    pub fn get_flags(&self) -> ParamFlags {
+        unsafe { from_glib((*self.pointer).flags) }
+    }
+
+    /// Returns the type of the values of the property
+    pub fn get_value_type(&self) -> Type {
+        unsafe { from_glib((*self.pointer).value_type) }
+    }
+
+    /// Returns the type that installed the property
+    pub fn get_owner_type(&self) -> Type {
+        unsafe { from_glib((*self.pointer).owner_type) }
+    }
+}
+
+impl Clone for ParamSpec {
+    fn clone(&self) -> ParamSpec {
+        unsafe { FromGlibPtrNotNull::borrow(self.pointer) }
+    }
+}
+
+impl Drop for ParamSpec {
+    fn drop(&mut self) {
+        unsafe { ffi::g_param_spec_unref(self.pointer) }
+    }
+}
+
+impl PartialEq for ParamSpec {
+    fn eq(&self, other: &ParamSpec) -> bool {
+        self.pointer == other.pointer
+    }
+}
+
+impl Eq for ParamSpec {}
+
+impl <'a> ToGlibPtr<'a, *mut ffi::C_GParamSpec> for ParamSpec {
+    type Storage = &'a ParamSpec;
+
+    #[inline]
+    fn borrow_to_glib(&'a self) -> Stash<*mut ffi::C_GParamSpec, ParamSpec> {
+        Stash(self.pointer, self)
+    }
+}
+
+impl FromGlibPtrNotNull<*mut ffi::C_GParamSpec> for ParamSpec {
+    unsafe fn borrow(ptr: *mut ffi::C_GParamSpec) -> ParamSpec {
+        debug_assert!(!ptr.is_null());
+        ParamSpec { pointer: ffi::g_param_spec_ref(ptr) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GParamSpec) -> ParamSpec {
+        debug_assert!(!ptr.is_null());
+        ParamSpec { pointer: ptr }
+    }
+
+    unsafe fn sink(ptr: *mut ffi::C_GParamSpec) -> ParamSpec {
+        debug_assert!(!ptr.is_null());
+        ParamSpec { pointer: ffi::g_param_spec_ref_sink(ptr) }
+    }
+}
+
+impl FromGlibPtr<*mut ffi::C_GParamSpec> for Option<ParamSpec> {
+    unsafe fn borrow(ptr: *mut ffi::C_GParamSpec) -> Option<ParamSpec> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::borrow(ptr)) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GParamSpec) -> Option<ParamSpec> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::take(ptr)) }
+    }
+
+    unsafe fn sink(ptr: *mut ffi::C_GParamSpec) -> Option<ParamSpec> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::sink(ptr)) }
+    }
+}
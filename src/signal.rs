@@ -2,23 +2,291 @@
 // See the COPYRIGHT file at the top-level directory of this distribution.
 // Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
 
-use libc::c_void;
+use std::mem;
+use std::slice;
+use libc::{c_uint, c_ulong, c_void};
 
+use closure::Closure;
+use error::SignalError;
 use ffi::{self, gpointer, GCallback};
-use translate::ToGlibPtr;
+use translate::{FromGlib, FromGlibPtrNotNull, ToGlib, ToGlibPtr, from_glib};
+use type_::Type;
 
-pub unsafe fn connect(receiver: gpointer, signal_name: &str, trampoline: GCallback,
-                      closure: *mut Box<Fn()>) -> u64 {
+/// The id of a handler connected to a signal
+///
+/// Returned when connecting, used to disconnect or block the handler.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct SignalHandlerId(c_ulong);
+
+impl ToGlib for SignalHandlerId {
+    type GlibType = c_ulong;
+
+    #[inline]
+    fn to_glib(&self) -> c_ulong {
+        self.0
+    }
+}
+
+impl FromGlib<c_ulong> for SignalHandlerId {
+    #[inline]
+    fn from_glib(val: c_ulong) -> SignalHandlerId {
+        assert!(val != 0);
+        SignalHandlerId(val)
+    }
+}
+
+bitflags! {
+    /// Flags of a signal
+    flags SignalFlags: u32 {
+        /// The class handler is invoked before the connected handlers
+        const SIGNAL_RUN_FIRST = ffi::G_SIGNAL_RUN_FIRST,
+        /// The class handler is invoked after the connected handlers
+        const SIGNAL_RUN_LAST = ffi::G_SIGNAL_RUN_LAST,
+        /// The class handler is invoked in the cleanup stage
+        const SIGNAL_RUN_CLEANUP = ffi::G_SIGNAL_RUN_CLEANUP,
+        /// Emitting the signal from one of its handlers restarts the emission
+        const SIGNAL_NO_RECURSE = ffi::G_SIGNAL_NO_RECURSE,
+        /// The signal supports `::detail` suffixes
+        const SIGNAL_DETAILED = ffi::G_SIGNAL_DETAILED,
+        /// The signal can be emitted by any code, not just the object
+        const SIGNAL_ACTION = ffi::G_SIGNAL_ACTION,
+        /// The signal has no emission hooks
+        const SIGNAL_NO_HOOKS = ffi::G_SIGNAL_NO_HOOKS,
+        /// Boxed arguments are copied for the handlers
+        const SIGNAL_MUST_COLLECT = ffi::G_SIGNAL_MUST_COLLECT,
+        /// The signal is deprecated
+        const SIGNAL_DEPRECATED = ffi::G_SIGNAL_DEPRECATED,
+    }
+}
+
+impl ToGlib for SignalFlags {
+    type GlibType = ffi::GSignalFlags;
+
+    #[inline]
+    fn to_glib(&self) -> ffi::GSignalFlags {
+        self.bits()
+    }
+}
+
+impl FromGlib<ffi::GSignalFlags> for SignalFlags {
+    #[inline]
+    fn from_glib(value: ffi::GSignalFlags) -> SignalFlags {
+        SignalFlags::from_bits_truncate(value)
+    }
+}
+
+/// Information about a signal
+#[derive(Clone, Debug)]
+pub struct SignalQuery {
+    signal_id: u32,
+    signal_name: String,
+    owner_type: Type,
+    flags: SignalFlags,
+    return_type: Type,
+    param_types: Vec<Type>,
+}
+
+impl SignalQuery {
+    /// Returns the id of the signal
+    pub fn get_signal_id(&self) -> u32 {
+        self.signal_id
+    }
+
+    /// Returns the name of the signal
+    pub fn get_signal_name(&self) -> &str {
+        &self.signal_name
+    }
+
+    /// Returns the type the signal is defined for
+    pub fn get_owner_type(&self) -> Type {
+        self.owner_type
+    }
+
+    /// Returns the flags of the signal
+    pub fn get_flags(&self) -> SignalFlags {
+        self.flags
+    }
+
+    /// Returns the return type of the handlers, `Type::Unit` for none
+    pub fn get_return_type(&self) -> Type {
+        self.return_type
+    }
+
+    /// Returns the types of the arguments, not including the instance
+    pub fn get_param_types(&self) -> &[Type] {
+        &self.param_types
+    }
+}
+
+/// Returns the id of the signal `name` of `type_`, if there is one
+pub fn lookup(name: &str, type_: Type) -> Option<u32> {
+    match unsafe { ffi::g_signal_lookup(name.borrow_to_glib().0, type_.to_glib()) } {
+        0 => None,
+        id => Some(id),
+    }
+}
+
+/// Returns information about the signal `signal_id`
+pub fn query(signal_id: u32) -> Option<SignalQuery> {
+    unsafe {
+        let mut query: ffi::C_GSignalQuery = mem::zeroed();
+        ffi::g_signal_query(signal_id, &mut query);
+        if query.signal_id == 0 {
+            return None;
+        }
+        let param_types = if query.n_params == 0 { &[][..] }
+            else { slice::from_raw_parts(query.param_types, query.n_params as usize) };
+        Some(SignalQuery {
+            signal_id: query.signal_id,
+            signal_name: FromGlibPtrNotNull::borrow(query.signal_name),
+            owner_type: from_glib(query.itype),
+            flags: from_glib(query.signal_flags),
+            return_type: from_glib(query.return_type & !ffi::G_SIGNAL_TYPE_STATIC_SCOPE),
+            param_types: param_types.iter()
+                .map(|&type_| from_glib(type_ & !ffi::G_SIGNAL_TYPE_STATIC_SCOPE))
+                .collect(),
+        })
+    }
+}
+
+/// Returns the ids of the signals defined by `type_`, not including the
+/// signals of its ancestors
+pub fn list_ids(type_: Type) -> Vec<u32> {
+    unsafe {
+        let mut n_ids = 0;
+        let ids = ffi::g_signal_list_ids(type_.to_glib(), &mut n_ids);
+        if ids.is_null() {
+            return Vec::new();
+        }
+        let res = slice::from_raw_parts(ids, n_ids as usize).to_vec();
+        ffi::g_free(ids as gpointer);
+        res
+    }
+}
+
+/// Parses a signal name with an optional `::detail` for `type_`
+///
+/// Returns the signal id and the detail quark, which is 0 for no detail. If
+/// `force_detail_quark` is `false`, a detail never seen before is also 0.
+pub fn parse_name(detailed_signal: &str, type_: Type, force_detail_quark: bool) -> Option<(u32, u32)> {
+    let (mut signal_id, mut detail): (c_uint, ffi::GQuark) = (0, 0);
+    let found: bool = unsafe {
+        from_glib(ffi::g_signal_parse_name(detailed_signal.borrow_to_glib().0, type_.to_glib(),
+            &mut signal_id, &mut detail, force_detail_quark.to_glib()))
+    };
+    if found { Some((signal_id, detail)) } else { None }
+}
+
+/// Fails if the type of `instance` has no signal `signal_name`
+pub unsafe fn check_signal(instance: gpointer, signal_name: &str) -> Result<(), SignalError> {
+    let type_: Type = from_glib((*(*(instance as *mut ffi::C_GTypeInstance)).g_class).g_type);
+    match parse_name(signal_name, type_, true) {
+        Some(_) => Ok(()),
+        None => Err(SignalError::NotFound { name: signal_name.to_string(), type_: type_ }),
+    }
+}
+
+/// Connects `closure` to the signal `signal_name` of `receiver`
+///
+/// `trampoline` is called with the signal arguments followed by `closure`.
+/// `closure` is freed when the handler is disconnected, or right away if
+/// `receiver` has no signal `signal_name`.
+pub unsafe fn connect<F: ?Sized>(receiver: gpointer, signal_name: &str, trampoline: GCallback,
+                                 closure: *mut Box<F>) -> Result<SignalHandlerId, SignalError> {
+    if let Err(err) = check_signal(receiver, signal_name) {
+        drop(Box::from_raw(closure));
+        return Err(err);
+    }
+    Ok(connect_unchecked(receiver, signal_name, trampoline, closure))
+}
+
+/// Like `connect`, but for signals that `receiver` is known to have
+///
+/// GLib only logs a warning if the signal doesn't exist, and `closure` is
+/// leaked.
+pub unsafe fn connect_unchecked<F: ?Sized>(receiver: gpointer, signal_name: &str, trampoline: GCallback,
+                                           closure: *mut Box<F>) -> SignalHandlerId {
     let handle = ffi::g_signal_connect_data(receiver, signal_name.borrow_to_glib().0,
-        trampoline, closure as gpointer, destroy_closure, 0) as u64;
-    assert!(handle > 0);
-    handle
+        trampoline, closure as gpointer, destroy_closure::<F>, 0);
+    from_glib(handle)
+}
+
+/// Connects `closure` to the signal `signal_name` of `receiver`
+///
+/// The closure gets the instance as the first parameter value, followed by
+/// the signal arguments. If `after` is `true`, it's called after the class
+/// handler of the signal. Fails if `receiver` has no signal `signal_name`.
+pub unsafe fn connect_closure(receiver: gpointer, signal_name: &str, closure: &Closure,
+                              after: bool) -> Result<SignalHandlerId, SignalError> {
+    check_signal(receiver, signal_name)?;
+    let handle = ffi::g_signal_connect_closure(receiver, signal_name.borrow_to_glib().0,
+        closure.borrow_to_glib().0, after.to_glib());
+    Ok(from_glib(handle))
 }
 
-extern "C" fn destroy_closure(ptr: *mut c_void, _: *mut c_void) {
+extern "C" fn destroy_closure<F: ?Sized>(ptr: *mut c_void, _: *mut c_void) {
     unsafe {
-        let ptr = ptr as *mut Box<Fn()>;
-        // destroy
-        Box::from_raw(ptr);
+        let ptr = ptr as *mut Box<F>;
+        drop(Box::from_raw(ptr));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use object::Object;
+    use traits::FFIGObject;
+    use type_::{GetType, Type};
+    use super::*;
+
+    struct DropGuard(Rc<Cell<bool>>);
+
+    impl Drop for DropGuard {
+        fn drop(&mut self) {
+            self.0.set(true);
+        }
+    }
+
+    #[test]
+    fn query_notify() {
+        let id = lookup("notify", Object::get_type()).unwrap();
+        assert!(list_ids(Object::get_type()).contains(&id));
+        assert_eq!(lookup("no-such-signal", Object::get_type()), None);
+
+        let query = query(id).unwrap();
+        assert_eq!(query.get_signal_id(), id);
+        assert_eq!(query.get_signal_name(), "notify");
+        assert_eq!(query.get_owner_type(), Object::get_type());
+        assert!(query.get_flags().contains(SIGNAL_DETAILED));
+        assert_eq!(query.get_return_type(), Type::Unit);
+        assert_eq!(query.get_param_types(), &[Type::BaseParamSpec]);
+
+        let (parsed_id, detail) = parse_name("notify::name", Object::get_type(), true).unwrap();
+        assert_eq!(parsed_id, id);
+        assert!(detail != 0);
+        assert_eq!(parse_name("notify", Object::get_type(), true), Some((id, 0)));
+        assert_eq!(parse_name("no-such-signal", Object::get_type(), true), None);
+    }
+
+    extern "C" fn noop() {}
+
+    #[test]
+    fn connect_unknown_signal() {
+        let object = Object::new(Object::get_type(), &[]).unwrap();
+        let closure = Closure::new(|_| None);
+        let err = unsafe {
+            connect_closure(object.unwrap_gobject() as gpointer, "no-such-signal", &closure, false)
+        };
+        assert_eq!(err, Err(SignalError::NotFound { name: "no-such-signal".to_string(),
+            type_: Object::get_type() }));
+
+        let dropped = Rc::new(Cell::new(false));
+        let guard = Box::new(Box::new(DropGuard(dropped.clone())));
+        let err = unsafe {
+            connect(object.unwrap_gobject() as gpointer, "no-such-signal", noop, Box::into_raw(guard))
+        };
+        assert!(err.is_err());
+        assert!(dropped.get());
     }
 }
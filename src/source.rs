@@ -0,0 +1,906 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+//! GSource — Event sources dispatched by a `MainContext`
+
+use std::cell::RefCell;
+use std::cmp;
+use std::mem;
+use std::ptr;
+use libc::c_int;
+#[cfg(unix)]
+use std::os::unix::io::RawFd;
+use ffi::{self, gpointer};
+use error::BoolError;
+use main_context::MainContext;
+use thread_guard::ThreadGuard;
+use translate::{FromGlib, FromGlibPtr, FromGlibPtrNotNull, Stash, ToGlib, ToGlibPtr, from_glib};
+
+/// The priority of a source, lower values are dispatched first
+pub type Priority = i32;
+
+/// Priority for high priority event sources
+pub const PRIORITY_HIGH: Priority = ffi::G_PRIORITY_HIGH;
+/// Priority for default priority event sources like timeouts
+pub const PRIORITY_DEFAULT: Priority = ffi::G_PRIORITY_DEFAULT;
+/// Priority for high priority idle functions
+pub const PRIORITY_HIGH_IDLE: Priority = ffi::G_PRIORITY_HIGH_IDLE;
+/// Priority for default priority idle functions
+pub const PRIORITY_DEFAULT_IDLE: Priority = ffi::G_PRIORITY_DEFAULT_IDLE;
+/// Priority for very low priority background tasks
+pub const PRIORITY_LOW: Priority = ffi::G_PRIORITY_LOW;
+
+/// The id of a source attached to a `MainContext`
+///
+/// Ids are only unique within a context, so the id keeps a reference to the
+/// context of its source. While an id is kept, its context and every source
+/// attached to it stay alive, even after all other `MainContext` references
+/// are dropped. Drop or `remove` ids of sources on private contexts to free
+/// them along with the context.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct SourceId {
+    id: u32,
+    context: MainContext,
+}
+
+impl SourceId {
+    fn new(id: u32, context: MainContext) -> SourceId {
+        assert!(id != 0);
+        SourceId { id: id, context: context }
+    }
+
+    /// Removes the source with this id from the context it was attached to
+    ///
+    /// Same as `g_source_remove`, but not limited to the default context.
+    /// The closure of the source is dropped. Fails if the source was
+    /// already removed, e.g. because its callback returned
+    /// `Continue(false)`.
+    pub fn remove(self) -> Result<(), BoolError> {
+        match self.context.find_source_by_id(&self) {
+            Some(source) => {
+                source.destroy();
+                Ok(())
+            }
+            None => Err(BoolError("Failed to remove source")),
+        }
+    }
+}
+
+impl ToGlib for SourceId {
+    type GlibType = u32;
+
+    #[inline]
+    fn to_glib(&self) -> u32 {
+        self.id
+    }
+}
+
+// The `g_*_add` functions attach to the global default context
+fn default_source_id(id: u32) -> SourceId {
+    SourceId::new(id, MainContext::default())
+}
+
+bitflags! {
+    /// The conditions a file descriptor can be watched for
+    flags IOCondition: u32 {
+        /// There is data to read
+        const IO_IN = ffi::G_IO_IN,
+        /// Data can be written without blocking
+        const IO_OUT = ffi::G_IO_OUT,
+        /// There is urgent data to read
+        const IO_PRI = ffi::G_IO_PRI,
+        /// Error condition
+        const IO_ERR = ffi::G_IO_ERR,
+        /// Hung up, the connection has been broken
+        const IO_HUP = ffi::G_IO_HUP,
+        /// Invalid request, the file descriptor is not open
+        const IO_NVAL = ffi::G_IO_NVAL,
+    }
+}
+
+impl ToGlib for IOCondition {
+    type GlibType = ffi::GIOCondition;
+
+    #[inline]
+    fn to_glib(&self) -> ffi::GIOCondition {
+        self.bits()
+    }
+}
+
+impl FromGlib<ffi::GIOCondition> for IOCondition {
+    #[inline]
+    fn from_glib(val: ffi::GIOCondition) -> IOCondition {
+        IOCondition::from_bits_truncate(val)
+    }
+}
+
+/// The id of a child process
+///
+/// On UNIX this is the process id, on Windows a process handle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pid(pub ffi::GPid);
+
+unsafe impl Send for Pid {}
+
+/// The return value of source callbacks
+///
+/// `Continue(false)` removes the source after the callback returns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Continue(pub bool);
+
+impl ToGlib for Continue {
+    type GlibType = ffi::Gboolean;
+
+    #[inline]
+    fn to_glib(&self) -> ffi::Gboolean {
+        self.0.to_glib()
+    }
+}
+
+// The `RefCell` turns a recursive dispatch of the same source into a panic
+// instead of two aliasing `&mut F`.
+fn into_raw<F>(func: F) -> gpointer {
+    let func: Box<RefCell<F>> = Box::new(RefCell::new(func));
+    Box::into_raw(func) as gpointer
+}
+
+extern "C" fn trampoline<F: FnMut() -> Continue + 'static>(func: gpointer) -> ffi::Gboolean {
+    unsafe {
+        let func = &*(func as *const RefCell<F>);
+        (&mut *func.borrow_mut())().to_glib()
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn trampoline_unix_fd<F>(fd: c_int, condition: ffi::GIOCondition,
+                                    func: gpointer) -> ffi::Gboolean
+where F: FnMut(RawFd, IOCondition) -> Continue + 'static {
+    unsafe {
+        let func = &*(func as *const RefCell<F>);
+        (&mut *func.borrow_mut())(fd, from_glib(condition)).to_glib()
+    }
+}
+
+extern "C" fn trampoline_child_watch<F>(pid: ffi::GPid, status: c_int, func: gpointer)
+where F: FnOnce(Pid, i32) + 'static {
+    unsafe {
+        let func = &*(func as *const RefCell<Option<F>>);
+        let func = func.borrow_mut().take().expect("Child watch callback called twice");
+        func(Pid(pid), status)
+    }
+}
+
+extern "C" fn destroy_closure<F>(ptr: gpointer) {
+    unsafe {
+        drop(Box::from_raw(ptr as *mut RefCell<F>));
+    }
+}
+
+/// Adds a closure to be called repeatedly every `interval` milliseconds
+/// by the default main context
+///
+/// The closure is dropped once it returns `Continue(false)` or the source is
+/// removed. Intervals are measured from the end of the previous dispatch,
+/// so the timing drifts under load.
+pub fn timeout_add<F>(interval: u32, func: F) -> SourceId
+where F: FnMut() -> Continue + Send + 'static {
+    timeout_add_to_context(&MainContext::default(), interval, func)
+}
+
+fn timeout_add_to_context<F>(context: &MainContext, interval: u32, func: F) -> SourceId
+where F: FnMut() -> Continue + Send + 'static {
+    timeout_source_new(interval, func).attach(context)
+}
+
+/// Adds a closure to be called repeatedly every `interval` seconds
+/// by the default main context
+///
+/// Unlike `timeout_add` this allows GLib to group timeouts so it can wake
+/// up less often; the first call happens after between `interval - 1` and
+/// `interval` seconds.
+pub fn timeout_add_seconds<F>(interval: u32, func: F) -> SourceId
+where F: FnMut() -> Continue + Send + 'static {
+    unsafe {
+        default_source_id(ffi::g_timeout_add_seconds_full(PRIORITY_DEFAULT, interval,
+            trampoline::<F>, into_raw(func), destroy_closure::<F>))
+    }
+}
+
+/// Adds a closure to be called whenever there are no higher priority events
+/// pending in the default main context
+///
+/// The closure is called from the thread running the default main context
+/// and dropped once it returns `Continue(false)` or the source is removed.
+pub fn idle_add<F>(func: F) -> SourceId
+where F: FnMut() -> Continue + Send + 'static {
+    idle_add_with_priority(PRIORITY_DEFAULT_IDLE, func)
+}
+
+/// Same as `idle_add` but with a custom priority
+pub fn idle_add_with_priority<F>(priority: Priority, func: F) -> SourceId
+where F: FnMut() -> Continue + Send + 'static {
+    unsafe {
+        default_source_id(ffi::g_idle_add_full(priority, trampoline::<F>, into_raw(func),
+            destroy_closure::<F>))
+    }
+}
+
+/// Adds a closure that doesn't need to be `Send` to be called whenever there
+/// are no higher priority events pending in the default main context
+///
+/// The calling thread must be able to own the default main context, the
+/// closure is then only ever called or dropped on this thread.
+///
+/// # Panics
+///
+/// Panics if the default main context is owned by another thread.
+///
+/// The process is aborted if the context is later dispatched from another
+/// thread, which would call or drop the closure there.
+pub fn idle_add_local<F>(func: F) -> SourceId
+where F: FnMut() -> Continue + 'static {
+    idle_add_local_with_priority(PRIORITY_DEFAULT_IDLE, func)
+}
+
+/// Same as `idle_add_local` but with a custom priority
+pub fn idle_add_local_with_priority<F>(priority: Priority, func: F) -> SourceId
+where F: FnMut() -> Continue + 'static {
+    idle_add_local_to_context(&MainContext::default(), priority, func)
+}
+
+fn idle_add_local_to_context<F>(context: &MainContext, priority: Priority, func: F) -> SourceId
+where F: FnMut() -> Continue + 'static {
+    let _acquire = context.acquire()
+        .expect("The main context is owned by another thread");
+    let mut func = ThreadGuard::new(func);
+    let source = idle_source_new(move || (func.get_mut())());
+    source.set_priority(priority);
+    source.attach(context)
+}
+
+/// Adds a closure to be called by the default main context whenever `fd`
+/// meets one of the conditions in `condition`
+///
+/// The closure receives the file descriptor and the conditions that were
+/// met; `IO_ERR`, `IO_HUP` and `IO_NVAL` may be reported even if they were
+/// not asked for. It is dropped once it returns `Continue(false)` or the
+/// source is removed. The file descriptor is not closed.
+#[cfg(unix)]
+pub fn unix_fd_add<F>(fd: RawFd, condition: IOCondition, func: F) -> SourceId
+where F: FnMut(RawFd, IOCondition) -> Continue + Send + 'static {
+    unsafe {
+        default_source_id(ffi::g_unix_fd_add_full(PRIORITY_DEFAULT, fd, condition.to_glib(),
+            trampoline_unix_fd::<F>, into_raw(func), destroy_closure::<F>))
+    }
+}
+
+/// Same as `unix_fd_add` for a closure that doesn't need to be `Send`
+///
+/// # Panics
+///
+/// Panics if the default main context is owned by another thread.
+///
+/// The process is aborted if the context is later dispatched from another
+/// thread, which would call or drop the closure there.
+#[cfg(unix)]
+pub fn unix_fd_add_local<F>(fd: RawFd, condition: IOCondition, func: F) -> SourceId
+where F: FnMut(RawFd, IOCondition) -> Continue + 'static {
+    let context = MainContext::default();
+    let _acquire = context.acquire()
+        .expect("The default main context is owned by another thread");
+    let mut func = ThreadGuard::new(func);
+    unix_fd_add(fd, condition, move |fd, condition| (func.get_mut())(fd, condition))
+}
+
+/// Adds a closure to be called by the default main context once the child
+/// process `pid` exits
+///
+/// The closure receives the pid and the wait status of the child, which on
+/// UNIX has to be decoded with the `WIFEXITED`/`WEXITSTATUS` family of
+/// macros. The child is reaped by GLib, `waitpid` must not be called on it
+/// and the process must not have been reaped already. On UNIX, `SIGCHLD`
+/// must not be ignored.
+pub fn child_watch_add<F>(pid: Pid, func: F) -> SourceId
+where F: FnOnce(Pid, i32) + Send + 'static {
+    unsafe {
+        default_source_id(ffi::g_child_watch_add_full(PRIORITY_DEFAULT, pid.0,
+            trampoline_child_watch::<F>, into_raw(Some(func)), destroy_closure::<Option<F>>))
+    }
+}
+
+/// Adds a closure to be called by the default main context whenever the
+/// UNIX signal `signum` is received
+///
+/// Only `SIGHUP`, `SIGINT`, `SIGTERM`, `SIGUSR1`, `SIGUSR2` and `SIGWINCH`
+/// are supported. Unlike a signal handler the closure runs as a normal
+/// callback of the main loop, so it has no restrictions on what it can do.
+/// It is dropped once it returns `Continue(false)` or the source is removed.
+/// When no source watches the signal anymore its default action is
+/// restored.
+#[cfg(unix)]
+pub fn unix_signal_add<F>(signum: i32, func: F) -> SourceId
+where F: FnMut() -> Continue + Send + 'static {
+    unsafe {
+        default_source_id(ffi::g_unix_signal_add_full(PRIORITY_DEFAULT, signum,
+            trampoline::<F>, into_raw(func), destroy_closure::<F>))
+    }
+}
+
+/// Same as `unix_signal_add` for a closure that doesn't need to be `Send`
+///
+/// # Panics
+///
+/// Panics if the default main context is owned by another thread.
+///
+/// The process is aborted if the context is later dispatched from another
+/// thread, which would call or drop the closure there.
+#[cfg(unix)]
+pub fn unix_signal_add_local<F>(signum: i32, func: F) -> SourceId
+where F: FnMut() -> Continue + 'static {
+    let context = MainContext::default();
+    let _acquire = context.acquire()
+        .expect("The default main context is owned by another thread");
+    let mut func = ThreadGuard::new(func);
+    unix_signal_add(signum, move || (func.get_mut())())
+}
+
+/// A reference counted `GSource`
+///
+/// A source is created unattached so it can be configured before `attach`
+/// hands it to a `MainContext`. Dropping a `Source` only drops a reference,
+/// an attached source stays active until it is destroyed or its callback
+/// returns `Continue(false)`.
+pub struct Source {
+    pointer: *mut ffi::C_GSource,
+}
+
+unsafe impl Send for Source {}
+unsafe impl Sync for Source {}
+
+impl Source {
+    /// Creates an unattached source driven by the `SourceFuncs`
+    /// implementation `funcs`
+    ///
+    /// `funcs` is stored in the same allocation as the `GSource` and dropped
+    /// when the last reference to the source goes away.
+    pub fn new<T: SourceFuncs>(funcs: T) -> Source {
+        unsafe {
+            let source_funcs = Box::new(ffi::C_GSourceFuncs {
+                prepare: Some(prepare::<T>),
+                check: Some(check::<T>),
+                dispatch: Some(dispatch::<T>),
+                finalize: Some(finalize::<T>),
+                closure_callback: None,
+                closure_marshal: None,
+            });
+            let source = ffi::g_source_new(Box::into_raw(source_funcs),
+                mem::size_of::<RustSource<T>>() as u32) as *mut RustSource<T>;
+            ptr::write(&mut (*source).funcs, Some(funcs));
+            FromGlibPtrNotNull::take(source as *mut ffi::C_GSource)
+        }
+    }
+
+    /// Attaches the source to `context` and returns its id in that context
+    pub fn attach(&self, context: &MainContext) -> SourceId {
+        let id = unsafe { ffi::g_source_attach(self.pointer, context.borrow_to_glib().0) };
+        SourceId::new(id, context.clone())
+    }
+
+    /// Removes the source from its context, its callback won't be called again
+    pub fn destroy(&self) {
+        unsafe { ffi::g_source_destroy(self.pointer) }
+    }
+
+    pub fn is_destroyed(&self) -> bool {
+        unsafe { from_glib(ffi::g_source_is_destroyed(self.pointer)) }
+    }
+
+    pub fn get_priority(&self) -> Priority {
+        unsafe { ffi::g_source_get_priority(self.pointer) }
+    }
+
+    pub fn set_priority(&self, priority: Priority) {
+        unsafe { ffi::g_source_set_priority(self.pointer, priority) }
+    }
+
+    pub fn get_name(&self) -> Option<String> {
+        unsafe { FromGlibPtr::borrow(ffi::g_source_get_name(self.pointer)) }
+    }
+
+    /// Sets a name that shows up in debugging and profiling tools
+    pub fn set_name(&self, name: &str) {
+        unsafe { ffi::g_source_set_name(self.pointer, name.borrow_to_glib().0) }
+    }
+
+    pub fn get_can_recurse(&self) -> bool {
+        unsafe { from_glib(ffi::g_source_get_can_recurse(self.pointer)) }
+    }
+
+    /// Sets whether the source may be dispatched while it's already
+    /// being dispatched by a nested main loop
+    pub fn set_can_recurse(&self, can_recurse: bool) {
+        unsafe { ffi::g_source_set_can_recurse(self.pointer, can_recurse.to_glib()) }
+    }
+
+    /// Returns the monotonic time at which the source will be dispatched,
+    /// or -1 if it isn't scheduled
+    pub fn get_ready_time(&self) -> i64 {
+        unsafe { ffi::g_source_get_ready_time(self.pointer) }
+    }
+
+    /// Schedules the source to be dispatched at the given monotonic time
+    /// in microseconds
+    ///
+    /// 0 dispatches it on the next iteration, -1 unschedules it. Can be
+    /// called from any thread.
+    pub fn set_ready_time(&self, ready_time: i64) {
+        unsafe { ffi::g_source_set_ready_time(self.pointer, ready_time) }
+    }
+
+    /// Returns the monotonic time of the current iteration of the source's
+    /// context in microseconds
+    pub fn get_time(&self) -> i64 {
+        unsafe { ffi::g_source_get_time(self.pointer) }
+    }
+
+    /// Returns the context the source is attached to, if any
+    pub fn get_context(&self) -> Option<MainContext> {
+        unsafe { FromGlibPtr::borrow(ffi::g_source_get_context(self.pointer)) }
+    }
+
+    /// Adds a child source that is attached and dispatched along with this
+    /// source
+    ///
+    /// Must be called before the source is attached.
+    pub fn add_child_source(&self, child_source: &Source) {
+        unsafe { ffi::g_source_add_child_source(self.pointer, child_source.pointer) }
+    }
+
+    pub fn remove_child_source(&self, child_source: &Source) {
+        unsafe { ffi::g_source_remove_child_source(self.pointer, child_source.pointer) }
+    }
+}
+
+/// A file descriptor added to a `Source` with `Source::add_unix_fd`
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct UnixFdTag(gpointer);
+
+#[cfg(unix)]
+unsafe impl Send for UnixFdTag {}
+
+#[cfg(unix)]
+impl Source {
+    /// Makes the context poll `fd` for `events` on behalf of the source
+    ///
+    /// This is meant for `SourceFuncs` implementations, which find out what
+    /// happened with `query_unix_fd` from their `check` or `dispatch`.
+    pub fn add_unix_fd(&self, fd: RawFd, events: IOCondition) -> UnixFdTag {
+        unsafe { UnixFdTag(ffi::g_source_add_unix_fd(self.pointer, fd, events.to_glib())) }
+    }
+
+    /// Changes the conditions a file descriptor of the source is polled for
+    pub fn modify_unix_fd(&self, tag: &UnixFdTag, new_events: IOCondition) {
+        unsafe { ffi::g_source_modify_unix_fd(self.pointer, tag.0, new_events.to_glib()) }
+    }
+
+    /// Stops polling a file descriptor of the source
+    pub fn remove_unix_fd(&self, tag: UnixFdTag) {
+        unsafe { ffi::g_source_remove_unix_fd(self.pointer, tag.0) }
+    }
+
+    /// Returns the conditions met by a file descriptor of the source during
+    /// the last poll
+    pub fn query_unix_fd(&self, tag: &UnixFdTag) -> IOCondition {
+        unsafe { from_glib(ffi::g_source_query_unix_fd(self.pointer, tag.0)) }
+    }
+}
+
+impl Clone for Source {
+    fn clone(&self) -> Source {
+        unsafe { FromGlibPtrNotNull::borrow(self.pointer) }
+    }
+}
+
+impl Drop for Source {
+    fn drop(&mut self) {
+        unsafe { ffi::g_source_unref(self.pointer) }
+    }
+}
+
+impl <'a> ToGlibPtr<'a, *mut ffi::C_GSource> for Source {
+    type Storage = &'a Source;
+
+    #[inline]
+    fn borrow_to_glib(&'a self) -> Stash<*mut ffi::C_GSource, Source> {
+        Stash(self.pointer, self)
+    }
+}
+
+impl FromGlibPtrNotNull<*mut ffi::C_GSource> for Source {
+    unsafe fn borrow(ptr: *mut ffi::C_GSource) -> Source {
+        debug_assert!(!ptr.is_null());
+        Source { pointer: ffi::g_source_ref(ptr) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GSource) -> Source {
+        debug_assert!(!ptr.is_null());
+        Source { pointer: ptr }
+    }
+}
+
+impl FromGlibPtr<*mut ffi::C_GSource> for Option<Source> {
+    unsafe fn borrow(ptr: *mut ffi::C_GSource) -> Option<Source> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::borrow(ptr)) }
+    }
+
+    unsafe fn take(ptr: *mut ffi::C_GSource) -> Option<Source> {
+        if ptr.is_null() { None }
+        else { Some(FromGlibPtrNotNull::take(ptr)) }
+    }
+}
+
+// A borrowed source pointer for state shared with other threads, which wake
+// the source up with `g_source_set_ready_time`. Whoever stores it must clear
+// it before the source is freed.
+pub(crate) struct SourcePtr(pub(crate) *mut ffi::C_GSource);
+
+unsafe impl Send for SourcePtr {}
+
+/// The implementation of a custom `Source`
+///
+/// GLib calls `prepare` before polling and `check` after polling on each
+/// iteration of the context the source is attached to, and `dispatch` if
+/// either of them reported the source as ready. All three are called from
+/// the thread iterating the context but may be called again recursively if
+/// the source can recurse, so state that changes is best kept in a `Cell`
+/// or `RefCell`.
+pub trait SourceFuncs: Send + 'static {
+    /// Called before the context polls
+    ///
+    /// Returns whether the source is ready to be dispatched without
+    /// polling, and if it isn't, the maximum time in milliseconds to poll
+    /// for. `None` places no limit on the poll, and times beyond
+    /// `i32::max_value()` are capped to it.
+    fn prepare(&self, _source: &Source) -> (bool, Option<u32>) {
+        (false, None)
+    }
+
+    /// Called after the context polled, returns whether the source is ready
+    /// to be dispatched
+    fn check(&self, _source: &Source) -> bool {
+        false
+    }
+
+    /// Handles the events of the source
+    ///
+    /// Returning `Continue(false)` destroys the source.
+    fn dispatch(&self, source: &Source) -> Continue;
+
+    /// Called when the source is finalized, right before `self` is dropped
+    fn finalize(&mut self) {
+    }
+}
+
+#[repr(C)]
+struct RustSource<T> {
+    source: ffi::C_GSource,
+    funcs: Option<T>,
+}
+
+// The callbacks only ever see a source that is alive, a `Source` is
+// borrowed without touching the reference count.
+unsafe fn with_source<T: SourceFuncs, R, F: FnOnce(&T, &Source) -> R>(source: *mut ffi::C_GSource,
+                                                                     func: F) -> R {
+    let funcs = (*(source as *const RustSource<T>)).funcs.as_ref().unwrap();
+    let source: Source = FromGlibPtrNotNull::take(source);
+    let res = func(funcs, &source);
+    mem::forget(source);
+    res
+}
+
+extern "C" fn prepare<T: SourceFuncs>(source: *mut ffi::C_GSource, timeout: *mut c_int) -> ffi::Gboolean {
+    unsafe {
+        let (ready, max_timeout) = with_source(source, |funcs: &T, source| funcs.prepare(source));
+        *timeout = max_timeout.map_or(-1, |t| cmp::min(t, c_int::max_value() as u32) as c_int);
+        ready.to_glib()
+    }
+}
+
+extern "C" fn check<T: SourceFuncs>(source: *mut ffi::C_GSource) -> ffi::Gboolean {
+    unsafe { with_source(source, |funcs: &T, source| funcs.check(source)).to_glib() }
+}
+
+extern "C" fn dispatch<T: SourceFuncs>(source: *mut ffi::C_GSource, _: Option<ffi::GSourceFunc>,
+                                       _: gpointer) -> ffi::Gboolean {
+    unsafe { with_source(source, |funcs: &T, source| funcs.dispatch(source)).to_glib() }
+}
+
+extern "C" fn finalize<T: SourceFuncs>(source: *mut ffi::C_GSource) {
+    unsafe {
+        let source = source as *mut RustSource<T>;
+        if let Some(mut funcs) = (*source).funcs.take() {
+            funcs.finalize();
+        }
+        // GLib doesn't look at the function table after finalizing
+        drop(Box::from_raw((*source).source.source_funcs as *mut ffi::C_GSourceFuncs));
+    }
+}
+
+unsafe fn source_with_callback<F>(source: *mut ffi::C_GSource, func: F) -> Source
+where F: FnMut() -> Continue + Send + 'static {
+    ffi::g_source_set_callback(source, trampoline::<F>, into_raw(func), destroy_closure::<F>);
+    FromGlibPtrNotNull::take(source)
+}
+
+/// Creates an unattached source calling `func` every `interval` milliseconds
+pub fn timeout_source_new<F>(interval: u32, func: F) -> Source
+where F: FnMut() -> Continue + Send + 'static {
+    unsafe { source_with_callback(ffi::g_timeout_source_new(interval), func) }
+}
+
+/// Creates an unattached source calling `func` every `interval` seconds
+pub fn timeout_source_new_seconds<F>(interval: u32, func: F) -> Source
+where F: FnMut() -> Continue + Send + 'static {
+    unsafe { source_with_callback(ffi::g_timeout_source_new_seconds(interval), func) }
+}
+
+/// Creates an unattached source calling `func` whenever `fd` meets one of
+/// the conditions in `condition`
+#[cfg(unix)]
+pub fn unix_fd_source_new<F>(fd: RawFd, condition: IOCondition, func: F) -> Source
+where F: FnMut(RawFd, IOCondition) -> Continue + Send + 'static {
+    unsafe {
+        let source = ffi::g_unix_fd_source_new(fd, condition.to_glib());
+        // GUnixFDSource calls its callback as a GUnixFDSourceFunc
+        ffi::g_source_set_callback(source, mem::transmute(trampoline_unix_fd::<F> as ffi::GUnixFDSourceFunc),
+            into_raw(func), destroy_closure::<F>);
+        FromGlibPtrNotNull::take(source)
+    }
+}
+
+/// Creates an unattached source calling `func` once the child process `pid`
+/// exits
+///
+/// See `child_watch_add` for the caveats.
+pub fn child_watch_source_new<F>(pid: Pid, func: F) -> Source
+where F: FnOnce(Pid, i32) + Send + 'static {
+    unsafe {
+        let source = ffi::g_child_watch_source_new(pid.0);
+        // GChildWatchSource calls its callback as a GChildWatchFunc
+        ffi::g_source_set_callback(source,
+            mem::transmute(trampoline_child_watch::<F> as ffi::GChildWatchFunc),
+            into_raw(Some(func)), destroy_closure::<Option<F>>);
+        FromGlibPtrNotNull::take(source)
+    }
+}
+
+/// Creates an unattached source calling `func` whenever the UNIX signal
+/// `signum` is received
+///
+/// See `unix_signal_add` for the supported signals.
+#[cfg(unix)]
+pub fn unix_signal_source_new<F>(signum: i32, func: F) -> Source
+where F: FnMut() -> Continue + Send + 'static {
+    unsafe { source_with_callback(ffi::g_unix_signal_source_new(signum), func) }
+}
+
+/// Creates an unattached source calling `func` when there are no higher
+/// priority events pending
+pub fn idle_source_new<F>(func: F) -> Source
+where F: FnMut() -> Continue + Send + 'static {
+    unsafe { source_with_callback(ffi::g_idle_source_new(), func) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use main_context::MainContext;
+    use super::*;
+
+    #[test]
+    fn idle_source_on_private_context() {
+        let context = MainContext::new();
+        let _acquire = context.acquire().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_clone = count.clone();
+        let source = idle_source_new(move || {
+            Continue(count_clone.fetch_add(1, Ordering::SeqCst) < 1)
+        });
+        source.set_name("test idle");
+        source.set_priority(PRIORITY_HIGH);
+        let source_id = source.attach(&context);
+
+        assert_eq!(source.get_name(), Some("test idle".to_string()));
+        assert_eq!(source.get_priority(), PRIORITY_HIGH);
+        assert!(source.get_context() == Some(context.clone()));
+        assert!(context.find_source_by_id(&source_id).is_some());
+
+        while context.iteration(false) {}
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert!(source.is_destroyed());
+    }
+
+    #[test]
+    fn remove_from_private_context() {
+        let default_source = idle_source_new(|| Continue(true));
+        let default_id = default_source.attach(&MainContext::default());
+
+        // ids are counted per context, attach until one collides
+        let context = MainContext::new();
+        let mut sources = Vec::new();
+        let id = loop {
+            let source = idle_source_new(|| Continue(true));
+            let id = source.attach(&context);
+            sources.push(source);
+            if id.to_glib() == default_id.to_glib() {
+                break id;
+            }
+        };
+
+        id.remove().unwrap();
+        assert!(sources.last().unwrap().is_destroyed());
+        assert!(!default_source.is_destroyed());
+        default_id.remove().unwrap();
+        assert!(default_source.is_destroyed());
+    }
+
+    #[test]
+    fn timeout_add_drops_closure() {
+        let context = MainContext::new();
+        let _acquire = context.acquire().unwrap();
+        let state = Arc::new(());
+        let state_clone = state.clone();
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_clone = count.clone();
+        timeout_add_to_context(&context, 1, move || {
+            let _ = &state_clone;
+            Continue(count_clone.fetch_add(1, Ordering::SeqCst) < 1)
+        });
+
+        assert_eq!(Arc::strong_count(&state), 2);
+        while count.load(Ordering::SeqCst) < 2 {
+            context.iteration(true);
+        }
+        assert_eq!(Arc::strong_count(&state), 1);
+    }
+
+    #[test]
+    fn idle_add_local_drops_closure() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let context = MainContext::new();
+        let _acquire = context.acquire().unwrap();
+        let count = Rc::new(Cell::new(0));
+        let count_clone = count.clone();
+        idle_add_local_to_context(&context, PRIORITY_DEFAULT_IDLE, move || {
+            count_clone.set(count_clone.get() + 1);
+            Continue(count_clone.get() < 3)
+        });
+
+        assert_eq!(Rc::strong_count(&count), 2);
+        while context.iteration(false) {}
+        assert_eq!(count.get(), 3);
+        assert_eq!(Rc::strong_count(&count), 1);
+    }
+
+    struct Countdown {
+        left: Arc<AtomicUsize>,
+    }
+
+    impl SourceFuncs for Countdown {
+        fn prepare(&self, _: &Source) -> (bool, Option<u32>) {
+            (true, None)
+        }
+
+        fn dispatch(&self, _: &Source) -> Continue {
+            Continue(self.left.fetch_sub(1, Ordering::SeqCst) > 1)
+        }
+
+        fn finalize(&mut self) {
+            self.left.store(100, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn custom_source() {
+        let context = MainContext::new();
+        let _acquire = context.acquire().unwrap();
+        let left = Arc::new(AtomicUsize::new(3));
+        let source = Source::new(Countdown { left: left.clone() });
+        source.attach(&context);
+
+        while context.iteration(false) {}
+        assert_eq!(left.load(Ordering::SeqCst), 0);
+        assert!(source.is_destroyed());
+        drop(source);
+        assert_eq!(left.load(Ordering::SeqCst), 100);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_fd_source() {
+        use std::io::Write;
+        use std::os::unix::io::AsRawFd;
+        use std::os::unix::net::UnixStream;
+
+        let context = MainContext::new();
+        let _acquire = context.acquire().unwrap();
+        let (mut writer, reader) = UnixStream::pair().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_clone = count.clone();
+        let reader_fd = reader.as_raw_fd();
+        let source = unix_fd_source_new(reader_fd, IO_IN, move |fd, condition| {
+            assert_eq!(fd, reader_fd);
+            assert!(condition.contains(IO_IN));
+            count_clone.fetch_add(1, Ordering::SeqCst);
+            Continue(false)
+        });
+        source.attach(&context);
+
+        assert!(!context.iteration(false));
+        writer.write_all(b"x").unwrap();
+        assert!(context.iteration(true));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(source.is_destroyed());
+    }
+
+    // the signal number is only known for Linux
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn unix_signal_source() {
+        use libc::c_int;
+
+        // not exported by libc 0.1
+        const SIGUSR1: c_int = 10;
+        extern "C" {
+            fn raise(sig: c_int) -> c_int;
+        }
+
+        let context = MainContext::new();
+        let _acquire = context.acquire().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let count_clone = count.clone();
+        let source = unix_signal_source_new(SIGUSR1, move || {
+            count_clone.fetch_add(1, Ordering::SeqCst);
+            Continue(false)
+        });
+        source.attach(&context);
+
+        assert_eq!(unsafe { raise(SIGUSR1) }, 0);
+        while !source.is_destroyed() {
+            context.iteration(true);
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        assert!(!context.iteration(false));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn child_watch_exit_status() {
+        use std::process::Command;
+        use std::sync::Mutex;
+
+        let context = MainContext::new();
+        let _acquire = context.acquire().unwrap();
+        let child = Command::new("sh").arg("-c").arg("exit 3").spawn().unwrap();
+        let pid = Pid(child.id() as ::ffi::GPid);
+        let result = Arc::new(Mutex::new(None));
+        let result_clone = result.clone();
+        let source = child_watch_source_new(pid, move |pid, status| {
+            *result_clone.lock().unwrap() = Some((pid, status));
+        });
+        source.attach(&context);
+
+        while !source.is_destroyed() {
+            context.iteration(true);
+        }
+        let (child_pid, status) = result.lock().unwrap().take().unwrap();
+        assert_eq!(child_pid, pid);
+        assert_eq!((status >> 8) & 0xff, 3);
+    }
+}
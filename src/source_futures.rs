@@ -0,0 +1,168 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+//! Timers as `Future`s and `Stream`s
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use futures_core::Stream;
+use main_context::MainContext;
+use source::{Continue, Source, timeout_source_new};
+
+struct TimerState {
+    ticks: usize,
+    waker: Option<Waker>,
+}
+
+// The source is only created on the first poll so that it's attached to the
+// thread-default context of the task, not of whoever created the future.
+struct Timer {
+    interval: u32,
+    repeat: bool,
+    source: Option<Source>,
+    state: Arc<Mutex<TimerState>>,
+}
+
+impl Timer {
+    fn new(interval: Duration, repeat: bool) -> Timer {
+        let millis = interval.as_secs().saturating_mul(1000)
+            .saturating_add((interval.subsec_nanos() / 1_000_000) as u64);
+        Timer {
+            interval: if millis > u32::max_value() as u64 { u32::max_value() } else { millis as u32 },
+            repeat: repeat,
+            source: None,
+            state: Arc::new(Mutex::new(TimerState { ticks: 0, waker: None })),
+        }
+    }
+
+    fn poll_tick(&mut self, cx: &mut Context) -> Poll<()> {
+        if self.source.is_none() {
+            let state = self.state.clone();
+            let repeat = self.repeat;
+            let source = timeout_source_new(self.interval, move || {
+                let mut state = state.lock().unwrap();
+                state.ticks += 1;
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+                Continue(repeat)
+            });
+            source.attach(&MainContext::thread_default());
+            self.source = Some(source);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        if state.ticks > 0 {
+            state.ticks -= 1;
+            Poll::Ready(())
+        }
+        else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if let Some(ref source) = self.source {
+            source.destroy();
+        }
+    }
+}
+
+/// A `Future` that completes once a timeout has elapsed
+///
+/// Created by `timeout_future`.
+pub struct TimeoutFuture {
+    timer: Timer,
+}
+
+impl Future for TimeoutFuture {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        self.timer.poll_tick(cx)
+    }
+}
+
+/// A `Stream` that yields each time an interval has elapsed
+///
+/// Created by `interval_stream`. The stream never ends.
+pub struct IntervalStream {
+    timer: Timer,
+}
+
+impl Stream for IntervalStream {
+    type Item = ();
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<()>> {
+        self.timer.poll_tick(cx).map(Some)
+    }
+}
+
+/// Creates a `Future` that completes after `value` has elapsed
+///
+/// The underlying timeout source is attached to the thread-default
+/// `MainContext` of the task when the future is first polled, and removed
+/// when the future is dropped. The precision is one millisecond.
+pub fn timeout_future(value: Duration) -> TimeoutFuture {
+    TimeoutFuture { timer: Timer::new(value, false) }
+}
+
+/// Creates a `Stream` that yields every time `value` has elapsed
+///
+/// The underlying timeout source is attached to the thread-default
+/// `MainContext` of the task when the stream is first polled, and removed
+/// when the stream is dropped. Ticks that elapse while the stream isn't
+/// polled are queued up.
+pub fn interval_stream(value: Duration) -> IntervalStream {
+    IntervalStream { timer: Timer::new(value, true) }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use std::time::{Duration, Instant};
+    use futures_core::Stream;
+    use main_context::MainContext;
+    use super::*;
+
+    struct TakeTwo(IntervalStream, usize);
+
+    impl Future for TakeTwo {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+            while self.1 < 2 {
+                match Pin::new(&mut self.0).poll_next(cx) {
+                    Poll::Ready(Some(())) => self.1 += 1,
+                    _ => return Poll::Pending,
+                }
+            }
+            Poll::Ready(())
+        }
+    }
+
+    #[test]
+    fn timeout() {
+        let context = MainContext::new();
+        let start = Instant::now();
+        context.block_on(timeout_future(Duration::from_millis(20)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn interval() {
+        let context = MainContext::new();
+        let start = Instant::now();
+        context.block_on(TakeTwo(interval_stream(Duration::from_millis(10)), 0));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
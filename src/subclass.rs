@@ -0,0 +1,534 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+//! Defining object types in Rust
+//!
+//! A type is defined by implementing `ObjectImpl` for the struct holding
+//! the Rust data of each instance and registered with `register_type`.
+//! The implementation is unsafe because each type needs a `TypeData` static
+//! of its own.
+//!
+//! ```ignore
+//! struct Counter {
+//!     count: Cell<i32>,
+//! }
+//!
+//! unsafe impl ObjectImpl for Counter {
+//!     fn type_name() -> &'static str { "MyCounter" }
+//!
+//!     fn type_data() -> &'static TypeData {
+//!         static DATA: TypeData = TypeData::new();
+//!         &DATA
+//!     }
+//!
+//!     fn new() -> Counter { Counter { count: Cell::new(0) } }
+//!
+//!     fn class_init(klass: &mut ObjectClass) {
+//!         klass.install_property(1, ParamSpec::int("count", "Count", "The count",
+//!             0, i32::max_value(), 0, PARAM_READWRITE));
+//!     }
+//!
+//!     fn set_property(&self, _: &Object, _: u32, value: &Value, _: &ParamSpec) {
+//!         self.count.set(value.get());
+//!     }
+//!
+//!     fn get_property(&self, _: &Object, _: u32, value: &mut Value, _: &ParamSpec) {
+//!         value.set(&self.count.get());
+//!     }
+//! }
+//!
+//! let counter = Object::new(register_type::<Counter>(), &[("count", &5)])?;
+//! assert_eq!(get_impl::<Counter>(&counter).count.get(), 5);
+//! ```
+
+use std::mem;
+use std::ops::Deref;
+use std::ptr;
+use std::sync::Once;
+use std::sync::atomic::{AtomicIsize, AtomicPtr, AtomicUsize, Ordering};
+use libc::{c_int, size_t};
+use ffi;
+use object::Object;
+use param_spec::ParamSpec;
+use signal::SignalFlags;
+use translate::{ToGlib, ToGlibPtr, from_glib, from_glib_none};
+use type_::{GetType, Type};
+use value::Value;
+
+/// The base of all classes
+#[repr(C)]
+pub struct TypeClass(ffi::C_GTypeClass);
+
+impl TypeClass {
+    /// Returns the type of the class
+    pub fn get_type(&self) -> Type {
+        from_glib(self.0.g_type)
+    }
+}
+
+/// The base of all instances
+#[repr(C)]
+pub struct TypeInstance(ffi::C_GTypeInstance);
+
+impl TypeInstance {
+    /// Returns the class of the instance
+    pub fn get_class(&self) -> &TypeClass {
+        unsafe { &*(self.0.g_class as *const TypeClass) }
+    }
+}
+
+/// The class of an object type defined in Rust
+///
+/// Passed to `ObjectImpl::class_init` to install properties and signals.
+#[repr(C)]
+pub struct ObjectClass(ffi::C_GObjectClass);
+
+impl ObjectClass {
+    /// Installs a property
+    ///
+    /// `id` identifies the property in `ObjectImpl::set_property` and
+    /// `get_property`, it must be greater than 0 and unique in the class.
+    pub fn install_property(&mut self, id: u32, pspec: ParamSpec) {
+        assert!(id > 0, "Property ids must be greater than 0");
+        unsafe { ffi::g_object_class_install_property(&mut self.0, id, pspec.borrow_to_glib().0) }
+    }
+
+    /// Adds a signal and returns its id
+    ///
+    /// The signal has no class handler.
+    pub fn add_signal(&mut self, name: &str, flags: SignalFlags, param_types: &[Type],
+                      return_type: Type) -> u32 {
+        let mut param_types: Vec<ffi::GType> = param_types.iter().map(|t| t.to_glib()).collect();
+        let id = unsafe {
+            ffi::g_signal_newv(name.borrow_to_glib().0, self.get_type().to_glib(), flags.to_glib(),
+                ptr::null_mut(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut(), return_type.to_glib(),
+                param_types.len() as u32, param_types.as_mut_ptr())
+        };
+        assert!(id != 0, "Failed to add signal '{}'", name);
+        id
+    }
+}
+
+impl Deref for ObjectClass {
+    type Target = TypeClass;
+
+    fn deref(&self) -> &TypeClass {
+        unsafe { &*(&self.0.g_type_class as *const ffi::C_GTypeClass as *const TypeClass) }
+    }
+}
+
+/// The Rust data of the instances of an object type defined in Rust
+///
+/// A value is created by `new` for each instance and dropped when the
+/// instance is finalized. The other methods are virtual methods of the
+/// class, which chain up to the parent class on their own.
+///
+/// # Safety
+///
+/// `type_data` must return a static that no other implementation returns.
+/// The type is registered once per `TypeData`, so a second implementation
+/// sharing it would get the first type, and `get_impl` would hand out the
+/// data of one as the other.
+pub unsafe trait ObjectImpl: Sized + 'static {
+    /// Returns the name of the type, which must be unique in the process
+    fn type_name() -> &'static str;
+
+    /// Returns the registration data of the type, a static declared in the
+    /// body of this method so that it belongs to this implementation alone
+    fn type_data() -> &'static TypeData;
+
+    /// Returns the type to derive from, `Object` by default
+    fn parent_type() -> Type {
+        Object::get_type()
+    }
+
+    /// Creates the data of a new instance
+    fn new() -> Self;
+
+    /// Installs the properties and signals of the class
+    fn class_init(_klass: &mut ObjectClass) {}
+
+    /// Called after all construct properties were set
+    fn constructed(&self, _object: &Object) {}
+
+    /// Drops references to other objects, can be called more than once
+    fn dispose(&self, _object: &Object) {}
+
+    /// Called before the data is dropped, the object can't be referenced
+    /// anymore
+    fn finalize(&self, _instance: &TypeInstance) {}
+
+    /// Sets the property `id` installed by `class_init`
+    fn set_property(&self, _object: &Object, _id: u32, _value: &Value, _pspec: &ParamSpec) {}
+
+    /// Sets `value`, initialized to the type of the property, to the value
+    /// of the property `id` installed by `class_init`
+    fn get_property(&self, _object: &Object, _id: u32, _value: &mut Value, _pspec: &ParamSpec) {}
+}
+
+/// The registration data of a type defined in Rust
+///
+/// Each `ObjectImpl` returns its own static from `type_data`, so the class
+/// and instance functions of the type find it without a lookup. Sharing a
+/// static between implementations is undefined behavior.
+pub struct TypeData {
+    registered: Once,
+    type_: AtomicUsize,
+    private_offset: AtomicIsize,
+    parent_class: AtomicPtr<ffi::C_GObjectClass>,
+}
+
+impl TypeData {
+    /// Creates the data of a type that isn't registered yet
+    pub const fn new() -> TypeData {
+        TypeData {
+            registered: Once::new(),
+            type_: AtomicUsize::new(0),
+            private_offset: AtomicIsize::new(0),
+            parent_class: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    fn get_type(&self) -> ffi::GType {
+        self.type_.load(Ordering::SeqCst) as ffi::GType
+    }
+
+    fn private_offset(&self) -> isize {
+        self.private_offset.load(Ordering::SeqCst)
+    }
+
+    fn parent_class(&self) -> *const ffi::C_GObjectClass {
+        self.parent_class.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for TypeData {
+    fn default() -> TypeData {
+        TypeData::new()
+    }
+}
+
+/// Registers the object type defined by `T`
+///
+/// The type is registered on the first call, later calls return the same
+/// type.
+///
+/// # Panics
+///
+/// Panics if the parent type isn't derived from `Object`, if another type
+/// with the same name exists or if `T` needs a larger alignment than GLib
+/// gives instance private data.
+pub fn register_type<T: ObjectImpl>() -> Type {
+    let data = T::type_data();
+    data.registered.call_once(|| {
+        let parent = T::parent_type();
+        assert!(parent.is_a(&Object::get_type()), "Type '{}' is not an object type", parent.name());
+        // GLib aligns the private data to two gsizes
+        assert!(mem::align_of::<Option<T>>() <= 2 * mem::size_of::<usize>(),
+            "Type '{}' needs an alignment of {} bytes", T::type_name(), mem::align_of::<Option<T>>());
+        unsafe {
+            let mut query: ffi::C_GTypeQuery = mem::zeroed();
+            ffi::g_type_query(parent.to_glib(), &mut query);
+            let info = ffi::C_GTypeInfo {
+                class_size: query.class_size as u16,
+                base_init: None,
+                base_finalize: None,
+                class_init: Some(class_init::<T>),
+                class_finalize: None,
+                class_data: ptr::null_mut(),
+                instance_size: query.instance_size as u16,
+                n_preallocs: 0,
+                instance_init: Some(instance_init::<T>),
+                value_table: ptr::null_mut(),
+            };
+            let type_ = ffi::g_type_register_static(parent.to_glib(), T::type_name().borrow_to_glib().0, &info, 0);
+            assert!(type_ != ffi::G_TYPE_INVALID, "Failed to register type '{}'", T::type_name());
+            let private_offset = ffi::g_type_add_instance_private(type_, mem::size_of::<Option<T>>() as size_t);
+            data.private_offset.store(private_offset as isize, Ordering::SeqCst);
+            data.type_.store(type_ as usize, Ordering::SeqCst);
+        }
+    });
+    from_glib(data.get_type())
+}
+
+/// Returns the Rust data of `object`
+///
+/// # Panics
+///
+/// Panics if `object` isn't an instance of the type registered for `T`.
+pub fn get_impl<T: ObjectImpl>(object: &Object) -> &T {
+    let data = T::type_data();
+    assert!(data.get_type() != ffi::G_TYPE_INVALID, "Type '{}' not registered", T::type_name());
+    assert!(object.get_object_type().is_a(&from_glib(data.get_type())),
+        "Object is not an instance of '{}'", T::type_name());
+    unsafe {
+        let private = get_private::<T>(object.borrow_to_glib().0, data);
+        (*private).as_ref().expect("Object already finalized")
+    }
+}
+
+unsafe fn get_private<T>(object: *mut ffi::C_GObject, data: &TypeData) -> *mut Option<T> {
+    (object as *mut u8).offset(data.private_offset()) as *mut Option<T>
+}
+
+extern "C" fn class_init<T: ObjectImpl>(klass: ffi::gpointer, _: ffi::gpointer) {
+    unsafe {
+        let data = T::type_data();
+        let mut private_offset = data.private_offset() as c_int;
+        ffi::g_type_class_adjust_private_offset(klass, &mut private_offset);
+        data.private_offset.store(private_offset as isize, Ordering::SeqCst);
+        data.parent_class.store(ffi::g_type_class_peek_parent(klass) as *mut ffi::C_GObjectClass, Ordering::SeqCst);
+
+        let klass = &mut *(klass as *mut ObjectClass);
+        klass.0.constructed = Some(constructed::<T>);
+        klass.0.dispose = Some(dispose::<T>);
+        klass.0.finalize = Some(finalize::<T>);
+        klass.0.set_property = Some(set_property::<T>);
+        klass.0.get_property = Some(get_property::<T>);
+        T::class_init(klass);
+    }
+}
+
+extern "C" fn instance_init<T: ObjectImpl>(instance: *mut ffi::C_GTypeInstance, _: ffi::gpointer) {
+    unsafe {
+        let data = T::type_data();
+        ptr::write(get_private::<T>(instance as *mut ffi::C_GObject, data), Some(T::new()));
+    }
+}
+
+extern "C" fn constructed<T: ObjectImpl>(object: *mut ffi::C_GObject) {
+    unsafe {
+        let data = T::type_data();
+        if let Some(f) = (*data.parent_class()).constructed {
+            f(object);
+        }
+        if let Some(ref imp) = *get_private::<T>(object, data) {
+            imp.constructed(&from_glib_none(object));
+        }
+    }
+}
+
+extern "C" fn dispose<T: ObjectImpl>(object: *mut ffi::C_GObject) {
+    unsafe {
+        let data = T::type_data();
+        if let Some(ref imp) = *get_private::<T>(object, data) {
+            imp.dispose(&from_glib_none(object));
+        }
+        if let Some(f) = (*data.parent_class()).dispose {
+            f(object);
+        }
+    }
+}
+
+extern "C" fn finalize<T: ObjectImpl>(object: *mut ffi::C_GObject) {
+    unsafe {
+        let data = T::type_data();
+        if let Some(imp) = (*get_private::<T>(object, data)).take() {
+            imp.finalize(&*(object as *const TypeInstance));
+        }
+        if let Some(f) = (*data.parent_class()).finalize {
+            f(object);
+        }
+    }
+}
+
+extern "C" fn set_property<T: ObjectImpl>(object: *mut ffi::C_GObject, id: u32, value: *const ffi::C_GValue,
+                                          pspec: *mut ffi::C_GParamSpec) {
+    unsafe {
+        let data = T::type_data();
+        if let Some(ref imp) = *get_private::<T>(object, data) {
+            imp.set_property(&from_glib_none(object), id, &*(value as *const Value), &from_glib_none(pspec));
+        }
+    }
+}
+
+extern "C" fn get_property<T: ObjectImpl>(object: *mut ffi::C_GObject, id: u32, value: *mut ffi::C_GValue,
+                                          pspec: *mut ffi::C_GParamSpec) {
+    unsafe {
+        let data = T::type_data();
+        if let Some(ref imp) = *get_private::<T>(object, data) {
+            imp.get_property(&from_glib_none(object), id, &mut *(value as *mut Value), &from_glib_none(pspec));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use object::{Object, ObjectExt};
+    use param_spec::{ParamSpec, PARAM_READWRITE};
+    use error::PropertyError;
+    use signal::SIGNAL_RUN_LAST;
+    use value::ToValue;
+    use super::*;
+
+    static FINALIZED: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counter {
+        count: Cell<i32>,
+        constructed: Cell<bool>,
+    }
+
+    unsafe impl ObjectImpl for Counter {
+        fn type_name() -> &'static str {
+            "RsTestCounter"
+        }
+
+        fn type_data() -> &'static TypeData {
+            static DATA: TypeData = TypeData::new();
+            &DATA
+        }
+
+        fn new() -> Counter {
+            Counter { count: Cell::new(0), constructed: Cell::new(false) }
+        }
+
+        fn class_init(klass: &mut ObjectClass) {
+            klass.install_property(1, ParamSpec::int("count", "Count", "The count",
+                0, 100, 0, PARAM_READWRITE));
+            klass.add_signal("add", SIGNAL_RUN_LAST, &[Type::I32], Type::Bool);
+        }
+
+        fn constructed(&self, _: &Object) {
+            self.constructed.set(true);
+        }
+
+        fn finalize(&self, _: &TypeInstance) {
+            FINALIZED.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn set_property(&self, _: &Object, id: u32, value: &Value, _: &ParamSpec) {
+            assert_eq!(id, 1);
+            self.count.set(value.get());
+        }
+
+        fn get_property(&self, _: &Object, id: u32, value: &mut Value, _: &ParamSpec) {
+            assert_eq!(id, 1);
+            value.set(&self.count.get());
+        }
+    }
+
+    #[test]
+    fn properties() {
+        let type_ = register_type::<Counter>();
+        assert_eq!(type_, register_type::<Counter>());
+        assert_eq!(type_.name(), "RsTestCounter");
+        assert_eq!(type_.parent(), Object::get_type());
+
+        let counter = Object::new(type_, &[("count", &5)]).unwrap();
+        assert!(get_impl::<Counter>(&counter).constructed.get());
+        assert_eq!(counter.get_property("count").unwrap().get::<i32>(), 5);
+
+        let notified = Rc::new(Cell::new(0));
+        let notified_clone = notified.clone();
+        counter.connect_notify(Some("count"), move |_, pspec| {
+            assert_eq!(pspec.get_name(), "count");
+            notified_clone.set(notified_clone.get() + 1);
+        });
+        counter.set_property("count", &7).unwrap();
+        assert_eq!(get_impl::<Counter>(&counter).count.get(), 7);
+        assert_eq!(notified.get(), 1);
+
+        {
+            let _freeze = counter.freeze_notify();
+            counter.set_property("count", &8).unwrap();
+            counter.notify("count");
+            assert_eq!(notified.get(), 1);
+        }
+        assert_eq!(notified.get(), 2);
+
+        match counter.set_property("count", &"text") {
+            Err(PropertyError::WrongType { .. }) => (),
+            _ => panic!("Expected a type mismatch"),
+        }
+
+        let last_pspec = Rc::new(RefCell::new(None));
+        let last_pspec_clone = last_pspec.clone();
+        counter.connect("notify", false, move |args| {
+            assert_eq!(args.len(), 2);
+            assert!(args[0].get::<Option<Object>>().is_some());
+            *last_pspec_clone.borrow_mut() = Some(args[1].value_type());
+            None
+        }).unwrap();
+        counter.set_property("count", &9).unwrap();
+        assert!(last_pspec.borrow().unwrap().is_a(&Type::BaseParamSpec));
+
+        let finalized = FINALIZED.load(Ordering::SeqCst);
+        drop(counter);
+        assert_eq!(FINALIZED.load(Ordering::SeqCst), finalized + 1);
+    }
+
+    #[test]
+    fn signal_handlers() {
+        let counter = Object::new(register_type::<Counter>(), &[]).unwrap();
+        let calls = Rc::new(Cell::new(0));
+        let calls_clone = calls.clone();
+        let id = counter.connect_notify(None, move |_, _| calls_clone.set(calls_clone.get() + 1));
+
+        counter.set_property("count", &1).unwrap();
+        assert_eq!(calls.get(), 1);
+        {
+            let _block = counter.block_signal_guard(&id);
+            counter.set_property("count", &2).unwrap();
+            assert_eq!(calls.get(), 1);
+        }
+        counter.set_property("count", &3).unwrap();
+        assert_eq!(calls.get(), 2);
+
+        assert!(counter.is_signal_handler_connected(&id));
+        counter.disconnect(id);
+        counter.set_property("count", &4).unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn emit() {
+        let counter = Object::new(register_type::<Counter>(), &[]).unwrap();
+        counter.connect("add", false, |args| {
+            let counter = args[0].get::<Option<Object>>().unwrap();
+            let imp = get_impl::<Counter>(&counter);
+            imp.count.set(imp.count.get() + args[1].get::<i32>());
+            Some(true.to_value())
+        }).unwrap();
+
+        let res = counter.emit_by_name("add", &[&5]).unwrap();
+        assert!(res.get::<bool>());
+        // transformed from u8
+        counter.emit_by_name("add", &[&2u8]);
+        assert_eq!(get_impl::<Counter>(&counter).count.get(), 7);
+    }
+
+    #[test]
+    #[should_panic]
+    fn emit_wrong_arguments() {
+        let counter = Object::new(register_type::<Counter>(), &[]).unwrap();
+        counter.emit_by_name("add", &[&1, &2]);
+    }
+
+    #[repr(align(64))]
+    struct Aligned;
+
+    unsafe impl ObjectImpl for Aligned {
+        fn type_name() -> &'static str {
+            "RsTestAligned"
+        }
+
+        fn type_data() -> &'static TypeData {
+            static DATA: TypeData = TypeData::new();
+            &DATA
+        }
+
+        fn new() -> Aligned {
+            Aligned
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn register_overaligned() {
+        register_type::<Aligned>();
+    }
+}
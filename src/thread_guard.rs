@@ -0,0 +1,51 @@
+// Copyright 2015, The Rust-GNOME Project Developers.
+// See the COPYRIGHT file at the top-level directory of this distribution.
+// Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
+
+use std::process;
+use std::thread::{self, ThreadId};
+use ffi;
+
+/// Wraps a value that may only be used and dropped by the thread that
+/// created it
+///
+/// This lets non-`Send` closures pass through GLib APIs that accept data
+/// from any thread. A wrong-thread access logs an error and aborts the
+/// process: it happens inside GLib callbacks, which can't unwind.
+pub struct ThreadGuard<T> {
+    thread_id: ThreadId,
+    value: T,
+}
+
+impl <T> ThreadGuard<T> {
+    pub fn new(value: T) -> ThreadGuard<T> {
+        ThreadGuard {
+            thread_id: thread::current().id(),
+            value: value,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.check();
+        &mut self.value
+    }
+
+    fn check(&self) {
+        if self.thread_id != thread::current().id() {
+            unsafe {
+                ffi::g_log(b"GLib-Rust\0".as_ptr() as *const _, ffi::G_LOG_LEVEL_ERROR, b"%s\0".as_ptr() as *const _,
+                    b"Value accessed from a different thread than where it was created\0".as_ptr());
+            }
+            // errors are fatal, but GLib doesn't tell the compiler
+            process::abort();
+        }
+    }
+}
+
+impl <T> Drop for ThreadGuard<T> {
+    fn drop(&mut self) {
+        self.check();
+    }
+}
+
+unsafe impl <T> Send for ThreadGuard<T> {}
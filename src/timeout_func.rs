@@ -3,19 +3,17 @@
 // Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
 
 pub mod timeout {
-    use ffi;
+    use source::{self, Continue, SourceId};
 
-    pub fn add<T>(interval: u32, func: fn(&mut T) -> i32, data: &T) -> u32 {
-        let tmp = data as *const T;
-        let tmp_f = func as ffi::gpointer;
-
-        unsafe { ffi::g_timeout_add(interval, tmp_f, tmp as ffi::gpointer) }
+    /// Same as `glib::timeout_add`
+    pub fn add<F>(interval: u32, func: F) -> SourceId
+    where F: FnMut() -> Continue + Send + 'static {
+        source::timeout_add(interval, func)
     }
 
-    pub fn add_seconds<T>(interval: u32, func: fn(&mut T) -> i32, data: &T) -> u32 {
-        let tmp = data as *const T;
-        let tmp_f = func as ffi::gpointer;
-
-        unsafe { ffi::g_timeout_add_seconds(interval, tmp_f, tmp as ffi::gpointer) }
+    /// Same as `glib::timeout_add_seconds`
+    pub fn add_seconds<F>(interval: u32, func: F) -> SourceId
+    where F: FnMut() -> Continue + Send + 'static {
+        source::timeout_add_seconds(interval, func)
     }
-}
\ No newline at end of file
+}
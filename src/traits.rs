@@ -3,63 +3,125 @@
 // Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
 
 use ffi;
-use std::any::Any;
-use translate::ToGlibPtr;
+use closure::Closure;
+use error::SignalError;
+use signal::{self, SignalHandlerId};
+use value::Value;
 
 pub trait FFIGObject {
     fn unwrap_gobject(&self) -> *mut ffi::C_GObject;
-    fn wrap_object(object: *mut ffi::C_GObject) -> Self;
+    /// Adds a reference to `object`, which must be a valid instance of the
+    /// wrapped type
+    unsafe fn wrap_object(object: *mut ffi::C_GObject) -> Self;
 }
 
-// pub trait Connect<T>: FFIGObject {
-//     fn connect<'a>(&self, signal: Box<Signal<'a>>) -> () {
-//         use std::mem::transmute;
-
-//         unsafe {
-//             let signal_name     = signal.get_signal_name().to_string();
-//             let trampoline      = signal.get_trampoline();
-
-//             let user_data_ptr   = transmute(Box::new(signal));
-
-//             signal_name.replace("_", "-").with_c_str(|signal_name| {
-//                 ffi::glue_signal_connect(
-//                     self.unwrap_gobject(),
-//                     signal_name,
-//                     Some(trampoline),
-//                     user_data_ptr
-//                 )
-//             });
-//         }
-//     }
-// }
-
-pub trait Signal<'a> {
+/// A handler for a signal
+///
+/// The handler is owned by the connection, so it can't borrow anything.
+pub trait Signal: 'static {
+    /// Returns the name of the signal, underscores are replaced by dashes
     fn get_signal_name(&self) -> &str;
 
-    fn get_trampoline(&self) -> extern "C" fn();
-
-    fn fetch_cb(&self) -> *mut FnMut();
+    /// Handles an emission of the signal
+    ///
+    /// `args` are the instance followed by the signal arguments. Returns the
+    /// return value, if the signal has one.
+    fn call(&self, args: &[Value]) -> Option<Value>;
+}
 
-    fn get_user_data(&'a self) -> &'a Option<Box<Any>>;
+pub trait Connect<T: Signal>: FFIGObject {
+    /// Connects `signal` to the object
+    ///
+    /// `signal` is dropped when the handler is disconnected or the object is
+    /// finalized. Fails if the object has no such signal.
+    fn connect(&self, signal: T) -> Result<SignalHandlerId, SignalError> {
+        let signal_name = signal.get_signal_name().replace("_", "-");
+        let closure = Closure::new(move |args| signal.call(args));
+        unsafe {
+            signal::connect_closure(self.unwrap_gobject() as ffi::gpointer, &signal_name, &closure, false)
+        }
+    }
 }
 
-pub trait Connect<'a, T: Signal<'a>>: FFIGObject {
-    fn connect(&self, signal: Box<T>) -> () {
-        use std::mem::transmute;
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use object::{Object, ObjectExt};
+    use param_spec::{ParamSpec, PARAM_READWRITE};
+    use type_::GetType;
+    use value::Value;
+    use super::*;
 
-        let signal = signal as Box<Signal<'a>>;
+    struct Notify(Rc<Cell<u32>>);
 
-        unsafe {
-            let trampoline      = signal.get_trampoline();
-            let signal_name = signal.get_signal_name().replace("_", "-");
-            let user_data_ptr   = transmute(Box::new(signal));
-            
-            ffi::glue_signal_connect(
-                self.unwrap_gobject(),
-                signal_name.borrow_to_glib().0,
-                Some(trampoline),
-                user_data_ptr
-            );
+    impl Signal for Notify {
+        fn get_signal_name(&self) -> &str {
+            "notify"
+        }
+
+        fn call(&self, _: &[Value]) -> Option<Value> {
+            self.0.set(self.0.get() + 1);
+            None
+        }
+    }
+
+    impl Drop for Notify {
+        fn drop(&mut self) {
+            self.0.set(100);
+        }
+    }
+
+    impl Connect<Notify> for Object {}
+
+    struct Bogus(Rc<Cell<bool>>);
+
+    impl Signal for Bogus {
+        fn get_signal_name(&self) -> &str {
+            "no_such_signal"
+        }
+
+        fn call(&self, _: &[Value]) -> Option<Value> {
+            None
+        }
+    }
+
+    impl Drop for Bogus {
+        fn drop(&mut self) {
+            self.0.set(true);
         }
     }
-}
\ No newline at end of file
+
+    impl Connect<Bogus> for Object {}
+
+    #[test]
+    fn connect() {
+        let object = Object::new(Object::get_type(), &[]).unwrap();
+        let pspec = ParamSpec::int("value", "Value", "A value", 0, 10, 0, PARAM_READWRITE);
+        let calls = Rc::new(Cell::new(0));
+
+        let id = Connect::connect(&object, Notify(calls.clone())).unwrap();
+        object.notify_by_pspec(&pspec);
+        assert_eq!(calls.get(), 1);
+        object.disconnect(id);
+        assert_eq!(calls.get(), 100);
+
+        calls.set(0);
+        Connect::connect(&object, Notify(calls.clone())).unwrap();
+        object.notify_by_pspec(&pspec);
+        assert_eq!(calls.get(), 1);
+        drop(object);
+        assert_eq!(calls.get(), 100);
+    }
+
+    #[test]
+    fn connect_unknown_signal() {
+        let object = Object::new(Object::get_type(), &[]).unwrap();
+        let dropped = Rc::new(Cell::new(false));
+        match Connect::connect(&object, Bogus(dropped.clone())) {
+            Err(SignalError::NotFound { ref name, .. }) if name == "no-such-signal" => (),
+            _ => panic!("Expected an unknown signal"),
+        }
+        assert!(dropped.get());
+    }
+}
@@ -32,6 +32,15 @@
 //!     }
 //! ```
 //!
+//! `from_glib_none` and `from_glib_full` are shorthands for borrowing and
+//! taking ownership of a pointer that can't be `NULL`.
+//!
+//! ```ignore
+//!     pub fn get_parent(&self) -> Object {
+//!         unsafe { from_glib_none(ffi::gtk_widget_get_parent(self.pointer)) }
+//!     }
+//! ```
+//!
 //! Letting the foreign library borrow pointers from the Rust side often
 //! requires having a temporary variable of an intermediate type (e.g. `CString`).
 //! A `Stash` contains the temporary storage and a pointer into it that
@@ -297,6 +306,26 @@ pub trait FromGlibPtrNotNull<P: Ptr>: Sized {
     }
 }
 
+/// Translate from a pointer type guaranteed to never be `NULL`, borrowing
+/// the reference
+///
+/// For reference counted types this adds a reference, the foreign side keeps
+/// its own.
+#[inline]
+pub unsafe fn from_glib_none<P: Ptr, T: FromGlibPtrNotNull<P>>(ptr: P) -> T {
+    FromGlibPtrNotNull::borrow(ptr)
+}
+
+/// Translate from a pointer type guaranteed to never be `NULL`, taking
+/// ownership of the reference
+///
+/// The foreign side gave up its reference, which is now released when the
+/// Rust value is dropped.
+#[inline]
+pub unsafe fn from_glib_full<P: Ptr, T: FromGlibPtrNotNull<P>>(ptr: P) -> T {
+    FromGlibPtrNotNull::take(ptr)
+}
+
 impl FromGlibPtr<*const c_char> for Option<String> {
     unsafe fn borrow(ptr: *const c_char) -> Option<String> {
         if ptr.is_null() { None }
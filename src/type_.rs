@@ -2,7 +2,7 @@
 // See the COPYRIGHT file at the top-level directory of this distribution.
 // Licensed under the MIT license, see the LICENSE file or <http://opensource.org/licenses/MIT>
 
-use translate::{FromGlib, ToGlib};
+use translate::{FromGlib, FromGlibPtr, ToGlib, from_glib};
 use ffi;
 
 /// A GLib or GLib-based library type
@@ -56,6 +56,25 @@ pub enum Type {
     Other(usize),
 }
 
+impl Type {
+    /// Returns the name of the type
+    pub fn name(&self) -> String {
+        let name: Option<String> = unsafe { FromGlibPtr::borrow(ffi::g_type_name(self.to_glib())) };
+        name.unwrap_or_else(|| "<invalid>".to_string())
+    }
+
+    /// Returns the parent of the type, `Invalid` for fundamental types
+    pub fn parent(&self) -> Type {
+        unsafe { from_glib(ffi::g_type_parent(self.to_glib())) }
+    }
+
+    /// Checks whether the type is `other`, derived from it or implements it
+    /// if `other` is an interface
+    pub fn is_a(&self, other: &Type) -> bool {
+        unsafe { from_glib(ffi::g_type_is_a(self.to_glib(), other.to_glib())) }
+    }
+}
+
 pub trait GetType {
     fn get_type() -> Type;
 }
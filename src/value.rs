@@ -5,9 +5,11 @@
 //! Generic values — A polymorphic type that can hold values of any other type
 
 use std::mem;
+use std::ptr;
 use libc::c_char;
 use ffi;
 use super::{to_bool, to_gboolean};
+use object::Object;
 use type_::Type;
 use translate::{FromGlibPtr, ToGlib, ToGlibPtr, from_glib};
 
@@ -17,6 +19,7 @@ pub trait ValuePublic {
 }
 
 // Possible improvment : store a function pointer inside the struct and make the struct templated
+#[repr(C)]
 pub struct Value {
     inner: ffi::C_GValue,
 }
@@ -26,6 +29,18 @@ impl Value {
         unsafe { Value { inner: mem::zeroed() } }
     }
 
+    /// Creates a value initialized to the default of `type_`
+    pub fn from_type(type_: Type) -> Value {
+        let mut value = Value::new();
+        value.init(type_);
+        value
+    }
+
+    /// Returns the type of the value, `Invalid` if it isn't initialized
+    pub fn value_type(&self) -> Type {
+        from_glib(self.inner.type_)
+    }
+
     pub fn init(&mut self, _type: Type) {
         unsafe { ffi::g_value_init(&mut self.inner, _type.to_glib()) }
     }
@@ -233,6 +248,17 @@ impl Value {
     }
 }
 
+impl Clone for Value {
+    fn clone(&self) -> Value {
+        let mut value = Value::new();
+        if self.value_type() != Type::Invalid {
+            value.init(self.value_type());
+            unsafe { ffi::g_value_copy(&self.inner, &mut value.inner) }
+        }
+        value
+    }
+}
+
 impl Drop for Value {
     fn drop(&mut self) {
         self.unset();
@@ -351,3 +377,69 @@ impl ValuePublic for String {
         gvalue.set_string(self.as_ref())
     }
 }
+
+impl ValuePublic for Option<Object> {
+    fn get(gvalue: &Value) -> Option<Object> {
+        unsafe { FromGlibPtr::borrow(ffi::g_value_get_object(&gvalue.inner) as *mut ffi::C_GObject) }
+    }
+
+    fn set(&self, gvalue: &mut Value) {
+        let ptr: *mut ffi::C_GObject = match *self {
+            Some(ref object) => object.borrow_to_glib().0,
+            None => ptr::null_mut(),
+        };
+        unsafe { ffi::g_value_set_object(&mut gvalue.inner, ptr as *const _) }
+    }
+}
+
+/// Converts to a `Value` of the corresponding type
+pub trait ToValue {
+    fn to_value(&self) -> Value;
+}
+
+macro_rules! to_value {
+    ($name:ty, $type_:expr) => {
+        impl ToValue for $name {
+            fn to_value(&self) -> Value {
+                let mut value = Value::from_type($type_);
+                value.set(self);
+                value
+            }
+        }
+    }
+}
+
+to_value!(bool, Type::Bool);
+to_value!(i8, Type::I8);
+to_value!(u8, Type::U8);
+to_value!(i32, Type::I32);
+to_value!(u32, Type::U32);
+to_value!(i64, Type::I64);
+to_value!(u64, Type::U64);
+to_value!(f32, Type::F32);
+to_value!(f64, Type::F64);
+to_value!(String, Type::String);
+to_value!(Type, unsafe { from_glib(ffi::g_gtype_get_type()) });
+
+impl ToValue for str {
+    fn to_value(&self) -> Value {
+        let mut value = Value::from_type(Type::String);
+        value.set_string(self);
+        value
+    }
+}
+
+impl <'a> ToValue for &'a str {
+    fn to_value(&self) -> Value {
+        (*self).to_value()
+    }
+}
+
+impl ToValue for Object {
+    /// Uses the runtime type of the object as the type of the value
+    fn to_value(&self) -> Value {
+        let mut value = Value::from_type(self.get_object_type());
+        value.set(&Some(self.clone()));
+        value
+    }
+}